@@ -2,6 +2,12 @@ use kopki::RenderInstance;
 
 fn main() {
     let instance = RenderInstance::new();
-    let device = instance.device_from_instance();
+    for adapter in instance.adapters() {
+        println!(
+            "{} ({:?}, {:?})",
+            adapter.name, adapter.device_type, adapter.backend
+        );
+    }
+    let device = instance.device_from_instance().unwrap();
     _ = device;
 }
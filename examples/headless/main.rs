@@ -0,0 +1,60 @@
+use kopki::reexports::wgpu;
+use kopki::{
+    FrameBuffer, RenderInstance, RenderTarget, ShapeRenderer, Sprite, SpriteBatch, StencilMode,
+    Text, TextAlign, TextRenderer,
+};
+
+fn main() {
+    let instance = RenderInstance::new();
+    let device = instance.headless_device().unwrap();
+    let mut framebuffer =
+        FrameBuffer::new_offscreen(&device, 320, 180, wgpu::TextureFormat::Rgba8UnormSrgb);
+    framebuffer.set_depth_format(Some(wgpu::TextureFormat::Depth24PlusStencil8));
+    framebuffer.set_sample_count(4).unwrap();
+    let mut shapes = ShapeRenderer::new(&device);
+    let mut text = TextRenderer::new(&device);
+    let mut sprites = SpriteBatch::new(&device);
+    let minimap = RenderTarget::new(
+        &device,
+        64,
+        64,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureUsages::empty(),
+    );
+
+    framebuffer
+        .renderable_texture()
+        .clear_pass(0.0, 1.0, 1.0, 1.0);
+
+    shapes.set_stencil(StencilMode::Write, 1);
+    shapes.fill_circle(160.0, 90.0, 40.0, wgpu::Color::WHITE);
+    shapes.set_stencil(StencilMode::Inside, 1);
+    shapes.fill_rect(120.0, 50.0, 80.0, 40.0, wgpu::Color::RED);
+    shapes.fill_rect(120.0, 90.0, 80.0, 40.0, wgpu::Color::BLUE);
+    shapes.set_stencil(StencilMode::Disabled, 0);
+    shapes.stroke_rounded_rect(20.0, 20.0, 280.0, 140.0, 12.0, 4.0, wgpu::Color::BLACK);
+    shapes.transform_stack().push();
+    shapes.transform_stack().translate(40.0, 40.0);
+    shapes.transform_stack().rotate(std::f32::consts::FRAC_PI_4);
+    shapes.fill_rect(-10.0, -10.0, 20.0, 20.0, wgpu::Color::GREEN);
+    shapes.transform_stack().pop();
+    shapes.flush(framebuffer.renderable_texture());
+
+    minimap.clear_pass(0.0, 0.0, 0.0, 0.5);
+    shapes.fill_circle(32.0, 32.0, 24.0, wgpu::Color::GREEN);
+    shapes.flush(&minimap);
+    sprites.draw(minimap.texture(), Sprite::new(248.0, 8.0));
+    sprites.flush(framebuffer.renderable_texture());
+
+    text.draw(
+        &Text::new("kopki headless", 20.0, 150.0)
+            .size(20.0)
+            .color(wgpu::Color::BLACK)
+            .align(TextAlign::Center)
+            .max_width(280.0),
+    );
+    text.flush(framebuffer.renderable_texture());
+
+    let pixels = framebuffer.renderable_texture().read_pixels().unwrap();
+    println!("first pixel: {:?}", &pixels[..4]);
+}
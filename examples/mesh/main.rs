@@ -0,0 +1,81 @@
+use kopki::app::{self, App, AppContext, WindowConfig};
+use kopki::reexports::winit::window::WindowId;
+use kopki::texture::{Texture, TextureOptions};
+use kopki::{Camera3D, Frame, Mat4, Material, Mesh, MeshRenderer};
+
+struct Meshes {
+    mesh: Mesh,
+    material: Material,
+    renderer: MeshRenderer,
+    angle: f32,
+}
+
+fn load_mesh(context: &AppContext) -> Mesh {
+    match std::env::args().nth(1) {
+        Some(path) => Mesh::from_obj_path(context.device(), path).unwrap(),
+        None => Mesh::cube(context.device(), 1.5),
+    }
+}
+
+fn load_material(context: &AppContext) -> Material {
+    let texture = Texture::from_image_bytes(
+        context.device(),
+        include_bytes!("../../resources/test.png"),
+        &TextureOptions::default(),
+    )
+    .unwrap();
+    Material::default().texture(&texture)
+}
+
+impl App for Meshes {
+    fn init(context: &mut AppContext) -> Self {
+        let mut renderer = MeshRenderer::new(context.device());
+        renderer.set_camera(
+            Camera3D::perspective(std::f32::consts::FRAC_PI_4, 800.0 / 600.0, 0.1, 100.0)
+                .look_at([0.0, 2.0, 5.0], [0.0, 0.0, 0.0]),
+        );
+        Meshes {
+            mesh: load_mesh(context),
+            material: load_material(context),
+            renderer,
+            angle: 0.0,
+        }
+    }
+    fn device_recreated(&mut self, context: &mut AppContext) {
+        let camera = *self.renderer.camera();
+        self.mesh = load_mesh(context);
+        self.material = load_material(context);
+        self.renderer = MeshRenderer::new(context.device());
+        self.renderer.set_camera(camera);
+    }
+    fn update(&mut self, _context: &mut AppContext, dt: f32) {
+        self.angle += dt;
+    }
+    fn draw(&mut self, _window: WindowId, frame: &mut Frame) {
+        self.renderer.draw(
+            &self.mesh,
+            &self.material,
+            Mat4::rotation_y(self.angle) * Mat4::rotation_x(self.angle * 0.5),
+        );
+
+        frame.clear_all(
+            wgpu::Color {
+                r: 0.1,
+                g: 0.1,
+                b: 0.1,
+                a: 1.0,
+            },
+            1.0,
+            0,
+        );
+        frame.draw(&mut self.renderer);
+    }
+}
+
+fn main() -> kopki::Result<()> {
+    app::run::<Meshes>(
+        WindowConfig::new("mesh")
+            .sample_count(4)
+            .depth_format(Some(wgpu::TextureFormat::Depth32Float)),
+    )
+}
@@ -2,44 +2,49 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use kopki::reexports::{wgpu, winit};
-use winit::{error::EventLoopError, event_loop::EventLoop, window::WindowBuilder};
-use kopki::{FrameBuffer, RenderSurface};
 use kopki::RenderInstance;
+use kopki::{FrameBuffer, RenderSurface, SurfaceOptions};
+use winit::{error::EventLoopError, event_loop::EventLoop, window::WindowBuilder};
 
 pub struct Window<'a> {
     window: Arc<winit::window::Window>,
     surface: RenderSurface<'a>,
-    framebuffer: FrameBuffer
+    framebuffer: FrameBuffer,
 }
 
 fn main() -> Result<(), EventLoopError> {
     let event_loop = EventLoop::new().unwrap();
     let render_instance = RenderInstance::new();
-    let render_device;
+    let mut render_device = None;
 
     let builder = WindowBuilder::new();
     let mut windows = HashMap::new();
-    {
-        let window = Arc::new(builder.clone().build(&event_loop).unwrap());
-        let surface = render_instance.surface_from_window(&window);
-        render_device = render_instance.device_from_surface(&surface);
-        let framebuffer = FrameBuffer::new(&render_device, &surface);
-        windows.insert(window.id(), Window {
-            window,
-            surface,
-            framebuffer
-        });
-    }
-    for _ in 0..3  {
+    for _ in 0..4 {
         let window = Arc::new(builder.clone().build(&event_loop).unwrap());
-        let surface = render_instance.surface_from_window(&window);
-        let framebuffer = FrameBuffer::new(&render_device, &surface);
-        windows.insert(window.id(), Window {
-            window,
-            surface: surface,
-            framebuffer
-        });
+        let (surface, device) = match &render_device {
+            Some(device) => {
+                let surface = render_instance
+                    .surface_for_device(&window, device, &SurfaceOptions::default())
+                    .unwrap();
+                (surface, device)
+            }
+            None => {
+                let surface = render_instance.surface_from_window(&window).unwrap();
+                let device = render_instance.device_from_surface(&surface).unwrap();
+                (surface, &*render_device.insert(device))
+            }
+        };
+        let framebuffer = FrameBuffer::new(device, &surface);
+        windows.insert(
+            window.id(),
+            Window {
+                window,
+                surface,
+                framebuffer,
+            },
+        );
     }
+    let render_device = render_device.unwrap();
 
     use winit::event::{Event, WindowEvent};
     event_loop.run(|event, elwt| match event {
@@ -57,34 +62,28 @@ fn main() -> Result<(), EventLoopError> {
                 let window = &windows[&window_id];
 
                 window.window.pre_present_notify();
-                let mut encoder =
-                    render_device
-                        .device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                            label: Some("Render Encoder"),
-                        });
-                window.framebuffer.renderable_texture().clear_pass_with_encoder(
-                    &mut encoder,
-                    0.0,
-                    1.0,
-                    1.0,
-                    0.0,
-                );
-                window.framebuffer.present_with_encoder(&window.surface, encoder);
+                match window.framebuffer.begin_frame(&window.surface) {
+                    Ok(mut frame) => frame.clear(wgpu::Color {
+                        r: 0.0,
+                        g: 1.0,
+                        b: 1.0,
+                        a: 0.0,
+                    }),
+                    Err(error) => eprintln!("failed to present: {}", error),
+                }
             }
             WindowEvent::CloseRequested => {
                 windows.remove(&window_id);
             }
             WindowEvent::Resized(size) => {
                 let window = windows.get_mut(&window_id).unwrap();
-                window.surface.resize(&render_device, size.width, size.height);
-                window.framebuffer.rebuild(&window.surface);
+                window
+                    .surface
+                    .resize(&render_device, size.width, size.height);
+                window.framebuffer.rebuild(&window.surface).unwrap();
             }
-            _ => ()
+            _ => (),
         },
-        Event::LoopExiting => {
-            
-        }
         _ => (),
     })?;
 
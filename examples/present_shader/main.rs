@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use kopki::reexports::winit::{error::EventLoopError, event_loop::EventLoop, window::Window};
+use kopki::RenderInstance;
+use kopki::{FrameBuffer, ShaderSource};
+
+const SHADER: &str = "
+struct Spotlight {
+    radius: f32,
+    softness: f32,
+};
+
+@group(1) @binding(1)
+var<uniform> spotlight: Spotlight;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = framebuffer_uv(position.xy);
+    let color = textureSample(tris_texture, tris_sampler, uv);
+    let distance = length(position.xy - global.mouse_position);
+    let light = 1.0 - smoothstep(spotlight.radius, spotlight.radius + spotlight.softness, distance);
+    let pulse = 0.75 + 0.25 * sin(global.time * 3.0);
+    return select(global.bar_color, vec4<f32>(color.rgb * light * pulse, color.a), inside_viewport(uv));
+}
+";
+
+fn main() -> Result<(), EventLoopError> {
+    let event_loop = EventLoop::new().unwrap();
+    let window = Arc::new(Window::new(&event_loop).unwrap());
+
+    let render_instance = RenderInstance::new();
+    let mut render_surface = Some(render_instance.surface_from_window(&window).unwrap());
+    let render_device = render_instance
+        .device_from_surface(render_surface.as_ref().unwrap())
+        .unwrap();
+    let mut framebuffer = FrameBuffer::new(&render_device, render_surface.as_ref().unwrap());
+    let source = match std::env::args().nth(1) {
+        Some(path) => ShaderSource::from_path(path).unwrap(),
+        None => ShaderSource::embedded(SHADER),
+    };
+    if let Err(error) = framebuffer.set_present_shader(source) {
+        eprintln!("{}", error);
+    }
+    framebuffer.set_present_uniforms(&[120.0f32, 40.0]);
+
+    use winit::event::{Event, WindowEvent};
+    event_loop.run(|event, elwt| match event {
+        Event::AboutToWait => {
+            window.request_redraw();
+        }
+        Event::WindowEvent { event, .. } => match event {
+            WindowEvent::RedrawRequested => {
+                match framebuffer.reload_shaders() {
+                    Ok(true) => println!("reloaded shaders"),
+                    Ok(false) => (),
+                    Err(error) => eprintln!("{}", error),
+                }
+                window.pre_present_notify();
+                framebuffer
+                    .renderable_texture()
+                    .clear_pass(1.0, 0.5, 0.0, 1.0);
+                if let Err(error) = framebuffer.present(render_surface.as_ref().unwrap()) {
+                    eprintln!("failed to present: {}", error);
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                framebuffer.set_mouse_position(position.x, position.y);
+            }
+            WindowEvent::Resized(size) => {
+                render_surface
+                    .as_mut()
+                    .unwrap()
+                    .resize(&render_device, size.width, size.height);
+                framebuffer
+                    .rebuild(render_surface.as_ref().unwrap())
+                    .unwrap();
+            }
+            WindowEvent::CloseRequested => elwt.exit(),
+            _ => (),
+        },
+        Event::LoopExiting => {
+            render_surface = None;
+        }
+        _ => (),
+    })?;
+
+    Ok(())
+}
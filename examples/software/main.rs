@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use kopki::reexports::winit::{error::EventLoopError, event_loop::EventLoop, window::Window};
+use kopki::RenderInstance;
+use kopki::{FrameBuffer, PostEffect, ScalingMode};
+
+fn main() -> Result<(), EventLoopError> {
+    let event_loop = EventLoop::new().unwrap();
+    let window = Arc::new(Window::new(&event_loop).unwrap());
+
+    let render_instance = RenderInstance::new();
+    let mut render_surface = Some(render_instance.surface_from_window(&window).unwrap());
+    let render_device = render_instance
+        .device_from_surface(render_surface.as_ref().unwrap())
+        .unwrap();
+    let mut framebuffer = FrameBuffer::with_fixed_size(
+        &render_device,
+        render_surface.as_ref().unwrap(),
+        320,
+        180,
+        ScalingMode::Integer,
+    );
+    framebuffer.set_filter(wgpu::FilterMode::Nearest);
+    framebuffer
+        .post_process_mut()
+        .push(PostEffect::crt(0.2, 0.04))
+        .unwrap();
+    framebuffer
+        .post_process_mut()
+        .push(PostEffect::vignette(0.6, 0.75, 0.4))
+        .unwrap();
+    let mut tick = 0u32;
+    let mut cursor = None;
+
+    use winit::event::{Event, WindowEvent};
+    event_loop.run(|event, elwt| match event {
+        Event::AboutToWait => {
+            window.request_redraw();
+        }
+        Event::WindowEvent { event, .. } => match event {
+            WindowEvent::RedrawRequested => {
+                tick = tick.wrapping_add(1);
+                let (width, height) = framebuffer.size();
+                let pixels = framebuffer.pixels_mut().unwrap();
+                for y in 0..height {
+                    for x in 0..width {
+                        let value = ((x ^ y).wrapping_add(tick) & 0xff) as u8;
+                        pixels.set_pixel(x, y, [value, 0, 255 - value, 255]);
+                    }
+                }
+                if let Some((x, y)) = cursor {
+                    pixels.set_pixel(x, y, [255, 255, 255, 255]);
+                }
+
+                window.pre_present_notify();
+                if let Err(error) = framebuffer.present(render_surface.as_ref().unwrap()) {
+                    eprintln!("failed to present: {}", error);
+                }
+            }
+            WindowEvent::Resized(size) => {
+                render_surface
+                    .as_mut()
+                    .unwrap()
+                    .resize(&render_device, size.width, size.height);
+                framebuffer
+                    .rebuild(render_surface.as_ref().unwrap())
+                    .unwrap();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                framebuffer.set_mouse_position(position.x, position.y);
+                cursor = framebuffer.window_to_framebuffer(
+                    render_surface.as_ref().unwrap(),
+                    position.x,
+                    position.y,
+                );
+            }
+            WindowEvent::CloseRequested => elwt.exit(),
+            _ => (),
+        },
+        Event::LoopExiting => {
+            render_surface = None;
+        }
+        _ => (),
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use kopki::app::{self, App, AppContext, WindowConfig};
+use kopki::reexports::winit::window::WindowId;
+use kopki::texture::{Texture, TextureOptions};
+use kopki::{BlendMode, Frame, ShapeRenderer, Sprite, SpriteBatch};
+
+struct Sprites {
+    texture: Texture,
+    sprites: SpriteBatch,
+    shapes: ShapeRenderer,
+    overlay: usize,
+    angle: f32,
+}
+
+fn add_overlay(context: &mut AppContext) -> usize {
+    let window = context.main_window().unwrap();
+    let framebuffer = context.framebuffer_mut(window).unwrap();
+    let overlay = framebuffer.add_layer(BlendMode::Additive);
+    framebuffer.layer_mut(overlay).set_opacity(0.5);
+    overlay
+}
+
+fn load_texture(context: &AppContext) -> Texture {
+    Texture::from_image_bytes(
+        context.device(),
+        include_bytes!("../../resources/test.png"),
+        &TextureOptions {
+            mipmaps: true,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+impl App for Sprites {
+    fn init(context: &mut AppContext) -> Self {
+        Sprites {
+            texture: load_texture(context),
+            sprites: SpriteBatch::new(context.device()),
+            shapes: ShapeRenderer::new(context.device()),
+            overlay: add_overlay(context),
+            angle: 0.0,
+        }
+    }
+    fn device_recreated(&mut self, context: &mut AppContext) {
+        self.texture = load_texture(context);
+        self.sprites = SpriteBatch::new(context.device());
+        self.shapes = ShapeRenderer::new(context.device());
+        self.overlay = add_overlay(context);
+    }
+    fn update(&mut self, _context: &mut AppContext, dt: f32) {
+        self.angle += dt * 0.6;
+    }
+    fn draw(&mut self, _window: WindowId, frame: &mut Frame) {
+        let (width, height) = frame.size();
+        for i in 0..1000 {
+            let t = i as f32 * 0.37 + self.angle;
+            let x = (t * 1.3).sin() * 0.5 + 0.5;
+            let y = (t * 0.7).cos() * 0.5 + 0.5;
+            self.sprites.draw(
+                &self.texture,
+                Sprite::new(x * width as f32, y * height as f32)
+                    .origin(128.0, 128.0)
+                    .scale(0.125, 0.125)
+                    .rotation(t),
+            );
+        }
+
+        frame.clear(wgpu::Color {
+            r: 0.1,
+            g: 0.1,
+            b: 0.1,
+            a: 1.0,
+        });
+        frame.draw(&mut self.sprites);
+
+        let radius = height as f32 * (0.3 + self.angle.sin() * 0.05);
+        self.shapes.fill_circle(
+            width as f32 * 0.5,
+            height as f32 * 0.5,
+            radius,
+            wgpu::Color {
+                r: 1.0,
+                g: 0.5,
+                b: 0.0,
+                a: 1.0,
+            },
+        );
+        frame.clear_layer(self.overlay, wgpu::Color::TRANSPARENT);
+        frame.draw_layer(self.overlay, &mut self.shapes);
+    }
+}
+
+fn main() -> kopki::Result<()> {
+    app::run::<Sprites>(WindowConfig::new("sprites"))
+}
@@ -1,19 +1,23 @@
 use std::sync::Arc;
 
 use kopki::reexports::winit::{error::EventLoopError, event_loop::EventLoop, window::Window};
-use kopki::FrameBuffer;
 use kopki::RenderInstance;
+use kopki::{FrameBuffer, Vsync};
 
 fn main() -> Result<(), EventLoopError> {
     let event_loop = EventLoop::new().unwrap();
     let window = Arc::new(Window::new(&event_loop).unwrap());
 
     let render_instance = RenderInstance::new();
-    let mut render_surface = Some(render_instance.surface_from_window(&window));
-    let render_device = render_instance.device_from_surface(&render_surface.as_ref().unwrap());
-    let mut framebuffer = FrameBuffer::new(&render_device, &render_surface.as_ref().unwrap());
+    let mut render_surface = Some(render_instance.surface_from_window(&window).unwrap());
+    let render_device = render_instance
+        .device_from_surface(render_surface.as_ref().unwrap())
+        .unwrap();
+    let mut framebuffer = FrameBuffer::new(&render_device, render_surface.as_ref().unwrap());
+    let mut vsync = Vsync::On;
 
     use winit::event::{Event, WindowEvent};
+    use winit::keyboard::Key;
     event_loop.run(|event, elwt| match event {
         Event::AboutToWait => {
             window.request_redraw();
@@ -21,27 +25,40 @@ fn main() -> Result<(), EventLoopError> {
         Event::WindowEvent { event, .. } => match event {
             WindowEvent::RedrawRequested => {
                 window.pre_present_notify();
-                let mut encoder =
-                    render_device
-                        .device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                            label: Some("Render Encoder"),
+                match framebuffer.begin_frame(render_surface.as_ref().unwrap()) {
+                    Ok(mut frame) => {
+                        frame.clear(wgpu::Color {
+                            r: 0.0,
+                            g: 1.0,
+                            b: 1.0,
+                            a: 0.0,
                         });
-                framebuffer.renderable_texture().clear_pass_with_encoder(
-                    &mut encoder,
-                    0.0,
-                    1.0,
-                    1.0,
-                    0.0,
-                );
-                framebuffer.present_with_encoder(render_surface.as_ref().unwrap(), encoder);
+                        frame.finish();
+                    }
+                    Err(error) => eprintln!("failed to present: {}", error),
+                }
             }
             WindowEvent::Resized(size) => {
                 render_surface
                     .as_mut()
                     .unwrap()
                     .resize(&render_device, size.width, size.height);
-                framebuffer.rebuild(&render_surface.as_ref().unwrap());
+                framebuffer
+                    .rebuild(render_surface.as_ref().unwrap())
+                    .unwrap();
+            }
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state.is_pressed()
+                    && event.logical_key.as_ref() == Key::Character("v") =>
+            {
+                vsync = match vsync {
+                    Vsync::On => Vsync::Off,
+                    Vsync::Off => Vsync::Adaptive,
+                    Vsync::Adaptive => Vsync::On,
+                };
+                let surface = render_surface.as_mut().unwrap();
+                surface.set_vsync(&render_device, vsync);
+                println!("{:?}: {:?}", vsync, surface.present_mode());
             }
             WindowEvent::CloseRequested => elwt.exit(),
             _ => (),
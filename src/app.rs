@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use winit::dpi::LogicalSize;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::{EventLoop, EventLoopWindowTarget};
+use winit::window::{Window, WindowBuilder, WindowId};
+
+use crate::frame::Frame;
+use crate::framebuffer::FrameBuffer;
+use crate::scaling::ScalingMode;
+use crate::surface::SurfaceOptions;
+use crate::{ArcedRenderDevice, Error, RenderInstance, RenderSurface, Result};
+
+pub trait App: Sized {
+    fn init(context: &mut AppContext) -> Self;
+    fn update(&mut self, _context: &mut AppContext, _dt: f32) {}
+    fn draw(&mut self, window: WindowId, frame: &mut Frame);
+    fn event(&mut self, _context: &mut AppContext, _window: WindowId, _event: &WindowEvent) {}
+    fn resized(&mut self, _context: &mut AppContext, _window: WindowId, _width: u32, _height: u32) {
+    }
+    fn device_recreated(&mut self, _context: &mut AppContext) {}
+    fn shader_error(&mut self, _context: &mut AppContext, _window: WindowId, _error: Error) {}
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+    pub surface: SurfaceOptions,
+    pub fixed_size: Option<(u32, u32, ScalingMode)>,
+    pub sample_count: u32,
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub hot_reload: bool,
+}
+
+pub struct AppContext<'a> {
+    target: &'a EventLoopWindowTarget<()>,
+    state: &'a mut AppState,
+}
+
+struct AppState {
+    instance: RenderInstance,
+    device: Option<ArcedRenderDevice>,
+    main_window: Option<WindowId>,
+    windows: HashMap<WindowId, AppWindow>,
+    error: Option<Error>,
+}
+
+struct AppWindow {
+    surface: RenderSurface<'static>,
+    framebuffer: FrameBuffer,
+    window: Arc<Window>,
+    config: WindowConfig,
+}
+
+impl WindowConfig {
+    pub fn new(title: &str) -> WindowConfig {
+        WindowConfig {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+    pub fn surface_options(mut self, surface: SurfaceOptions) -> Self {
+        self.surface = surface;
+        self
+    }
+    pub fn fixed_size(mut self, width: u32, height: u32, scaling_mode: ScalingMode) -> Self {
+        self.fixed_size = Some((width, height, scaling_mode));
+        self
+    }
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+    pub fn depth_format(mut self, depth_format: Option<wgpu::TextureFormat>) -> Self {
+        self.depth_format = depth_format;
+        self
+    }
+    pub fn hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: String::from("kopki"),
+            width: 800,
+            height: 600,
+            resizable: true,
+            surface: SurfaceOptions::default(),
+            fixed_size: None,
+            sample_count: 1,
+            depth_format: None,
+            hot_reload: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl<'a> AppContext<'a> {
+    pub fn instance(&self) -> &RenderInstance {
+        &self.state.instance
+    }
+    pub fn device(&self) -> &ArcedRenderDevice {
+        self.state.device.as_ref().unwrap()
+    }
+    pub fn main_window(&self) -> Option<WindowId> {
+        self.state.main_window
+    }
+    pub fn window_ids(&self) -> Vec<WindowId> {
+        self.state.windows.keys().copied().collect()
+    }
+    pub fn window(&self, id: WindowId) -> Option<&Arc<Window>> {
+        self.state.windows.get(&id).map(|window| &window.window)
+    }
+    pub fn surface(&self, id: WindowId) -> Option<&RenderSurface<'static>> {
+        self.state.windows.get(&id).map(|window| &window.surface)
+    }
+    pub fn framebuffer(&self, id: WindowId) -> Option<&FrameBuffer> {
+        self.state
+            .windows
+            .get(&id)
+            .map(|window| &window.framebuffer)
+    }
+    pub fn framebuffer_mut(&mut self, id: WindowId) -> Option<&mut FrameBuffer> {
+        self.state
+            .windows
+            .get_mut(&id)
+            .map(|window| &mut window.framebuffer)
+    }
+    pub fn create_window(&mut self, config: &WindowConfig) -> Result<WindowId> {
+        let window = Arc::new(
+            WindowBuilder::new()
+                .with_title(config.title.as_str())
+                .with_inner_size(LogicalSize::new(config.width, config.height))
+                .with_resizable(config.resizable)
+                .build(self.target)?,
+        );
+        let (surface, device) = match &self.state.device {
+            Some(device) => (
+                self.state
+                    .instance
+                    .surface_for_device(&window, device, &config.surface)?,
+                device.clone(),
+            ),
+            None => {
+                let surface = self
+                    .state
+                    .instance
+                    .surface_from_window_with_options(&window, &config.surface)?;
+                let device = self.state.instance.device_from_surface(&surface)?;
+                (surface, self.state.device.insert(device).clone())
+            }
+        };
+        let framebuffer = create_framebuffer(&device, &surface, config)?;
+
+        let id = window.id();
+        self.state.main_window.get_or_insert(id);
+        self.state.windows.insert(
+            id,
+            AppWindow {
+                surface,
+                framebuffer,
+                window,
+                config: config.clone(),
+            },
+        );
+        Ok(id)
+    }
+    pub fn close_window(&mut self, id: WindowId) {
+        self.state.windows.remove(&id);
+    }
+    pub fn exit(&self) {
+        self.target.exit();
+    }
+    fn recreate_device(&mut self) -> Result<()> {
+        let main_window = self
+            .state
+            .main_window
+            .and_then(|id| self.state.windows.get(&id))
+            .or_else(|| self.state.windows.values().next());
+        let device = match main_window {
+            Some(window) => self.state.instance.device_from_surface(&window.surface)?,
+            None => return Ok(()),
+        };
+        for window in self.state.windows.values_mut() {
+            window.surface.reconfigure(&device);
+            window.framebuffer = window.framebuffer.recreate(&device)?;
+        }
+        self.state.device = Some(device);
+        Ok(())
+    }
+    fn resize_window(&mut self, id: WindowId, width: u32, height: u32) {
+        let device = self.device().clone();
+        let result = match self.state.windows.get_mut(&id) {
+            Some(window) => {
+                window.surface.resize(&device, width, height);
+                window.framebuffer.rebuild(&window.surface)
+            }
+            None => Ok(()),
+        };
+        if let Err(error) = result {
+            self.state.error = Some(error);
+            self.exit();
+        }
+    }
+    fn reload_shaders<A: App>(&mut self, app: &mut A) {
+        let errors = self
+            .state
+            .windows
+            .iter_mut()
+            .filter(|(_, window)| window.config.hot_reload)
+            .filter_map(|(&id, window)| {
+                window
+                    .framebuffer
+                    .reload_shaders()
+                    .err()
+                    .map(|error| (id, error))
+            })
+            .collect::<Vec<_>>();
+        for (id, error) in errors {
+            app.shader_error(self, id, error);
+        }
+    }
+    fn redraw<A: App>(&mut self, app: &mut A, id: WindowId) {
+        let window = match self.state.windows.get(&id) {
+            Some(window) => window,
+            None => return,
+        };
+        let size = window.window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        window.window.pre_present_notify();
+        let error = match window.framebuffer.begin_frame(&window.surface) {
+            Ok(mut frame) => {
+                app.draw(id, &mut frame);
+                frame.finish();
+                return;
+            }
+            Err(error) => error,
+        };
+        match error {
+            Error::SurfaceLost | Error::SurfaceOutdated => {
+                self.resize_window(id, size.width, size.height);
+            }
+            Error::SurfaceTimeout | Error::DeviceLost => (),
+            error => {
+                self.state.error = Some(error);
+                self.exit();
+            }
+        }
+    }
+}
+
+pub fn run<A: App>(config: WindowConfig) -> Result<()> {
+    let event_loop = EventLoop::new()?;
+    let mut state = AppState {
+        instance: RenderInstance::new(),
+        device: None,
+        main_window: None,
+        windows: HashMap::new(),
+        error: None,
+    };
+    let mut app = {
+        let mut context = AppContext {
+            target: &event_loop,
+            state: &mut state,
+        };
+        context.create_window(&config)?;
+        A::init(&mut context)
+    };
+    let mut last_update = Instant::now();
+
+    event_loop.run(|event, target| {
+        let mut context = AppContext {
+            target,
+            state: &mut state,
+        };
+        match event {
+            Event::AboutToWait => {
+                if context.device().is_lost() {
+                    if let Err(error) = context.recreate_device() {
+                        context.state.error = Some(error);
+                        target.exit();
+                        return;
+                    }
+                    app.device_recreated(&mut context);
+                }
+                context.reload_shaders(&mut app);
+                let now = Instant::now();
+                app.update(&mut context, (now - last_update).as_secs_f32());
+                last_update = now;
+                for window in context.state.windows.values() {
+                    window.window.request_redraw();
+                }
+            }
+            Event::WindowEvent { window_id, event } => {
+                if !context.state.windows.contains_key(&window_id) {
+                    return;
+                }
+                app.event(&mut context, window_id, &event);
+                match event {
+                    WindowEvent::Resized(size) => {
+                        context.resize_window(window_id, size.width, size.height);
+                        app.resized(&mut context, window_id, size.width, size.height);
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        if let Some(framebuffer) = context.framebuffer_mut(window_id) {
+                            framebuffer.set_mouse_position(position.x, position.y);
+                        }
+                    }
+                    WindowEvent::RedrawRequested => context.redraw(&mut app, window_id),
+                    WindowEvent::CloseRequested => context.close_window(window_id),
+                    _ => (),
+                }
+            }
+            Event::LoopExiting => context.state.windows.clear(),
+            _ => (),
+        }
+        if context.state.windows.is_empty() {
+            target.exit();
+        }
+    })?;
+
+    match state.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn create_framebuffer(
+    device: &ArcedRenderDevice,
+    surface: &RenderSurface,
+    config: &WindowConfig,
+) -> Result<FrameBuffer> {
+    if let Some(format) = config.depth_format {
+        if !format.is_depth_stencil_format() {
+            return Err(Error::UnsupportedFormat(format));
+        }
+    }
+    let supported = std::iter::once(surface.format)
+        .chain(config.depth_format)
+        .all(|format| device.supports_sample_count(format, config.sample_count));
+    if !supported {
+        return Err(Error::UnsupportedSampleCount(config.sample_count));
+    }
+
+    let mut framebuffer = match config.fixed_size {
+        Some((width, height, scaling_mode)) => {
+            FrameBuffer::with_fixed_size(device, surface, width, height, scaling_mode)
+        }
+        None => FrameBuffer::new(device, surface),
+    };
+    framebuffer.set_sample_count(config.sample_count)?;
+    framebuffer.set_depth_format(config.depth_format);
+    Ok(framebuffer)
+}
@@ -0,0 +1,210 @@
+use crate::math::Rect;
+use crate::sprite::Sprite;
+use crate::texture::{rgba8_len, Texture};
+use crate::{ArcedRenderDevice, Error, Result};
+
+struct AtlasImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+struct Packing {
+    width: u32,
+    height: u32,
+    positions: Vec<(u32, u32)>,
+}
+
+pub struct TextureAtlasBuilder {
+    images: Vec<AtlasImage>,
+    padding: u32,
+    max_size: u32,
+}
+
+pub struct TextureAtlas {
+    texture: Texture,
+    regions: Vec<Rect>,
+}
+
+impl TextureAtlasBuilder {
+    pub fn new() -> TextureAtlasBuilder {
+        TextureAtlasBuilder {
+            images: Vec::new(),
+            padding: 1,
+            max_size: 8192,
+        }
+    }
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+    pub fn add(&mut self, width: u32, height: u32, pixels: &[u8]) -> Result<usize> {
+        if Some(pixels.len()) != rgba8_len(width, height) {
+            return Err(Error::InvalidPixelData {
+                width,
+                height,
+                len: pixels.len(),
+            });
+        }
+        self.images.push(AtlasImage {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        });
+        Ok(self.images.len() - 1)
+    }
+    pub fn build(&self, device: &ArcedRenderDevice) -> Result<TextureAtlas> {
+        let Packing {
+            width,
+            height,
+            positions,
+        } = self.pack().ok_or(Error::AtlasTooLarge)?;
+
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for (image, &(x, y)) in self.images.iter().zip(&positions) {
+            let row_bytes = (image.width * 4) as usize;
+            for row in 0..image.height {
+                let source = (row * image.width * 4) as usize;
+                let destination = (((y + row) * width + x) * 4) as usize;
+                pixels[destination..destination + row_bytes]
+                    .copy_from_slice(&image.pixels[source..source + row_bytes]);
+            }
+        }
+
+        let texture = Texture::from_rgba8(device, width, height, &pixels)?;
+        let regions = self
+            .images
+            .iter()
+            .zip(positions)
+            .map(|(image, (x, y))| {
+                Rect::new(x as f32, y as f32, image.width as f32, image.height as f32)
+            })
+            .collect();
+
+        Ok(TextureAtlas { texture, regions })
+    }
+    fn pack(&self) -> Option<Packing> {
+        let padding = self.padding;
+        let widest = self
+            .images
+            .iter()
+            .map(|image| image.width + padding * 2)
+            .max()
+            .unwrap_or(1);
+        let area: u64 = self
+            .images
+            .iter()
+            .map(|image| ((image.width + padding) * (image.height + padding)) as u64)
+            .sum();
+        let mut width = widest.max((area as f64).sqrt() as u32).next_power_of_two();
+
+        let mut order = (0..self.images.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.images[b].height.cmp(&self.images[a].height));
+
+        while width <= self.max_size {
+            let mut positions = vec![(0, 0); self.images.len()];
+            let (mut x, mut y, mut shelf_height) = (padding, padding, 0);
+            for &index in &order {
+                let image = &self.images[index];
+                if x + image.width + padding > width {
+                    x = padding;
+                    y += shelf_height + padding;
+                    shelf_height = 0;
+                }
+                positions[index] = (x, y);
+                x += image.width + padding;
+                shelf_height = shelf_height.max(image.height);
+            }
+            let height = (y + shelf_height + padding).max(1);
+            if height <= width {
+                return Some(Packing {
+                    width,
+                    height,
+                    positions,
+                });
+            }
+            width *= 2;
+        }
+
+        None
+    }
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextureAtlas {
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    pub fn region(&self, index: usize) -> Rect {
+        self.regions[index]
+    }
+    pub fn regions(&self) -> &[Rect] {
+        &self.regions
+    }
+    pub fn sprite(&self, index: usize, x: f32, y: f32) -> Sprite {
+        Sprite::new(x, y).source(self.regions[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_rejects_wrong_pixel_counts() {
+        let mut builder = TextureAtlasBuilder::new();
+        assert_eq!(builder.add(2, 2, &[0; 16]).unwrap(), 0);
+        assert!(matches!(
+            builder.add(2, 2, &[0; 15]),
+            Err(Error::InvalidPixelData {
+                width: 2,
+                height: 2,
+                len: 15
+            })
+        ));
+        assert!(matches!(
+            builder.add(u32::MAX, u32::MAX, &[]),
+            Err(Error::InvalidPixelData { .. })
+        ));
+        assert_eq!(builder.images.len(), 1);
+    }
+
+    #[test]
+    fn pack_keeps_images_apart() {
+        let mut builder = TextureAtlasBuilder::new().padding(2);
+        for (width, height) in [(10, 4), (3, 8), (6, 6), (1, 1)] {
+            builder
+                .add(width, height, &vec![0; (width * height * 4) as usize])
+                .unwrap();
+        }
+        let packing = builder.pack().unwrap();
+        let rects = builder
+            .images
+            .iter()
+            .zip(&packing.positions)
+            .map(|(image, &(x, y))| (x, y, x + image.width, y + image.height))
+            .collect::<Vec<_>>();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.0 >= 2 && a.1 >= 2 && a.2 + 2 <= packing.width && a.3 + 2 <= packing.height);
+            for b in &rects[i + 1..] {
+                assert!(a.2 + 2 <= b.0 || b.2 + 2 <= a.0 || a.3 + 2 <= b.1 || b.3 + 2 <= a.1);
+            }
+        }
+    }
+
+    #[test]
+    fn pack_fails_beyond_max_size() {
+        let mut builder = TextureAtlasBuilder::new().max_size(16);
+        builder.add(20, 1, &[0; 80]).unwrap();
+        assert!(builder.pack().is_none());
+    }
+}
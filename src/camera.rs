@@ -0,0 +1,305 @@
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::math::{Mat4, Rect, Transform2D};
+use crate::RenderDevice;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    pub position: [f32; 2],
+    pub zoom: f32,
+    pub rotation: f32,
+    pub viewport: Rect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective { fov_y: f32, near: f32, far: f32 },
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera3D {
+    pub eye: [f32; 3],
+    pub target: [f32; 3],
+    pub up: [f32; 3],
+    pub aspect: f32,
+    pub projection: Projection,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraUniform {
+    view_projection: [[f32; 4]; 4],
+}
+
+impl Camera2D {
+    pub fn new(width: f32, height: f32) -> Camera2D {
+        Camera2D {
+            position: [width / 2.0, height / 2.0],
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport: Rect::new(0.0, 0.0, width, height),
+        }
+    }
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = [x, y];
+        self
+    }
+    pub fn zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+    pub fn viewport(mut self, viewport: Rect) -> Self {
+        self.viewport = viewport;
+        self
+    }
+    pub fn view(&self) -> Transform2D {
+        Transform2D::scale(self.zoom, self.zoom)
+            * Transform2D::rotation(-self.rotation)
+            * Transform2D::translation(-self.position[0], -self.position[1])
+    }
+    pub fn view_projection(&self) -> [[f32; 4]; 4] {
+        self.clipped_view_projection(self.viewport)
+    }
+    pub fn clipped_viewport(&self, width: u32, height: u32) -> Option<Rect> {
+        let x = self.viewport.x.max(0.0);
+        let y = self.viewport.y.max(0.0);
+        let right = self.viewport.right().min(width as f32);
+        let bottom = self.viewport.bottom().min(height as f32);
+        (right > x && bottom > y).then(|| Rect::new(x, y, right - x, bottom - y))
+    }
+    pub fn clipped_view_projection(&self, clip: Rect) -> [[f32; 4]; 4] {
+        let offset = [
+            self.viewport.x + self.viewport.width / 2.0 - clip.x - clip.width / 2.0,
+            self.viewport.y + self.viewport.height / 2.0 - clip.y - clip.height / 2.0,
+        ];
+        let projection = Transform2D::scale(2.0 / clip.width, -2.0 / clip.height)
+            * Transform2D::translation(offset[0], offset[1]);
+        (projection * self.view()).to_cols_array_2d()
+    }
+    pub fn world_to_screen(&self, point: [f32; 2]) -> [f32; 2] {
+        let [x, y] = self.view().transform_point(point);
+        [
+            x + self.viewport.x + self.viewport.width / 2.0,
+            y + self.viewport.y + self.viewport.height / 2.0,
+        ]
+    }
+    pub fn screen_to_world(&self, point: [f32; 2]) -> [f32; 2] {
+        let local = [
+            point[0] - self.viewport.x - self.viewport.width / 2.0,
+            point[1] - self.viewport.y - self.viewport.height / 2.0,
+        ];
+        self.view().inverse().transform_point(local)
+    }
+}
+
+impl Camera3D {
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Camera3D {
+        Camera3D::new(aspect, Projection::Perspective { fov_y, near, far })
+    }
+    pub fn orthographic(height: f32, aspect: f32, near: f32, far: f32) -> Camera3D {
+        Camera3D::new(aspect, Projection::Orthographic { height, near, far })
+    }
+    fn new(aspect: f32, projection: Projection) -> Camera3D {
+        Camera3D {
+            eye: [0.0, 0.0, 5.0],
+            target: [0.0, 0.0, 0.0],
+            up: [0.0, 1.0, 0.0],
+            aspect,
+            projection,
+        }
+    }
+    pub fn look_at(mut self, eye: [f32; 3], target: [f32; 3]) -> Self {
+        self.eye = eye;
+        self.target = target;
+        self
+    }
+    pub fn up(mut self, up: [f32; 3]) -> Self {
+        self.up = up;
+        self
+    }
+    pub fn aspect(mut self, aspect: f32) -> Self {
+        self.aspect = aspect;
+        self
+    }
+    pub fn set_aspect(&mut self, width: u32, height: u32) {
+        self.aspect = width as f32 / height.max(1) as f32;
+    }
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at(self.eye, self.target, self.up)
+    }
+    pub fn projection(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y, near, far } => {
+                Mat4::perspective(fov_y, self.aspect, near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * self.aspect;
+                Mat4::orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        }
+    }
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection() * self.view()
+    }
+}
+
+pub(crate) fn create_camera_bind_group_layout(device: &RenderDevice) -> wgpu::BindGroupLayout {
+    device
+        .device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        })
+}
+
+pub(crate) fn create_camera_bind_group(
+    device: &RenderDevice,
+    layout: &wgpu::BindGroupLayout,
+    camera: &Camera2D,
+    viewport: Rect,
+) -> wgpu::BindGroup {
+    let uniform = CameraUniform {
+        view_projection: camera.clipped_view_projection(viewport),
+    };
+    let buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Camera Buffer"),
+        contents: bytemuck::bytes_of(&uniform),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+    device.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Camera Bind Group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    })
+}
+
+pub(crate) fn set_viewport(render_pass: &mut wgpu::RenderPass, viewport: Rect) {
+    render_pass.set_viewport(
+        viewport.x,
+        viewport.y,
+        viewport.width,
+        viewport.height,
+        0.0,
+        1.0,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 2], expected: [f32; 2]) {
+        assert!(
+            (actual[0] - expected[0]).abs() < 1e-3 && (actual[1] - expected[1]).abs() < 1e-3,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn camera_position_maps_to_viewport_center() {
+        let camera = Camera2D::new(800.0, 600.0);
+        assert_close(camera.world_to_screen([400.0, 300.0]), [400.0, 300.0]);
+        assert_close(camera.world_to_screen([0.0, 0.0]), [0.0, 0.0]);
+
+        let camera = camera
+            .position(100.0, 50.0)
+            .viewport(Rect::new(10.0, 20.0, 400.0, 300.0));
+        assert_close(camera.world_to_screen([100.0, 50.0]), [210.0, 170.0]);
+    }
+
+    #[test]
+    fn camera_zoom_and_rotation() {
+        let camera = Camera2D::new(800.0, 600.0).position(0.0, 0.0).zoom(2.0);
+        assert_close(camera.world_to_screen([10.0, 0.0]), [420.0, 300.0]);
+
+        let camera = camera.rotation(std::f32::consts::FRAC_PI_2);
+        assert_close(camera.world_to_screen([10.0, 0.0]), [400.0, 280.0]);
+    }
+
+    #[test]
+    fn world_screen_round_trip() {
+        let camera = Camera2D::new(640.0, 480.0)
+            .position(-35.0, 120.0)
+            .zoom(1.75)
+            .rotation(0.4)
+            .viewport(Rect::new(32.0, 16.0, 320.0, 240.0));
+        for point in [[0.0, 0.0], [-35.0, 120.0], [250.0, -80.0]] {
+            assert_close(camera.screen_to_world(camera.world_to_screen(point)), point);
+        }
+        for point in [[32.0, 16.0], [192.0, 136.0], [400.0, 300.0]] {
+            assert_close(camera.world_to_screen(camera.screen_to_world(point)), point);
+        }
+    }
+
+    #[test]
+    fn view_projection_maps_viewport_to_clip_space() {
+        let camera = Camera2D::new(800.0, 600.0);
+        let view_projection = Mat4 {
+            cols: camera.view_projection(),
+        };
+        let clip = |point: [f32; 2]| {
+            let [x, y, _] = view_projection.transform_point([point[0], point[1], 0.0]);
+            [x, y]
+        };
+        assert_close(clip([400.0, 300.0]), [0.0, 0.0]);
+        assert_close(clip([0.0, 0.0]), [-1.0, 1.0]);
+        assert_close(clip([800.0, 600.0]), [1.0, -1.0]);
+    }
+
+    #[test]
+    fn camera_larger_than_target_is_clipped() {
+        let camera = Camera2D::new(1600.0, 1200.0).position(300.0, 200.0);
+        let clip = camera.clipped_viewport(800, 600).unwrap();
+        assert_eq!(clip, Rect::new(0.0, 0.0, 800.0, 600.0));
+
+        let view_projection = Mat4 {
+            cols: camera.clipped_view_projection(clip),
+        };
+        for point in [[300.0, 200.0], [-100.0, -100.0], [450.0, 20.0]] {
+            let [x, y, _] = view_projection.transform_point([point[0], point[1], 0.0]);
+            let [screen_x, screen_y] = camera.world_to_screen(point);
+            assert_close(
+                [x, y],
+                [
+                    screen_x / clip.width * 2.0 - 1.0,
+                    1.0 - screen_y / clip.height * 2.0,
+                ],
+            );
+        }
+
+        let camera = camera.viewport(Rect::new(-100.0, 500.0, 400.0, 400.0));
+        assert_eq!(
+            camera.clipped_viewport(800, 600),
+            Some(Rect::new(0.0, 500.0, 300.0, 100.0))
+        );
+        let camera = camera.viewport(Rect::new(900.0, 0.0, 100.0, 100.0));
+        assert_eq!(camera.clipped_viewport(800, 600), None);
+    }
+}
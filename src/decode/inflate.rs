@@ -0,0 +1,348 @@
+const MAX_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u32) -> Result<u32, &'static str> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("unexpected end of data")?;
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+        Ok(value)
+    }
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0u16; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, &'static str> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err("invalid huffman code")
+    }
+}
+
+pub(crate) fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, &'static str> {
+    if data.len() < 6 {
+        return Err("zlib stream is too short");
+    }
+    let (cmf, flags) = (data[0], data[1]);
+    if cmf & 0x0f != 8 || (cmf as u16 * 256 + flags as u16) % 31 != 0 {
+        return Err("invalid zlib header");
+    }
+    if flags & 0x20 != 0 {
+        return Err("zlib preset dictionaries are not supported");
+    }
+    let output = inflate(&data[2..], limit)?;
+    let checksum = u32::from_be_bytes(data[data.len() - 4..].try_into().unwrap());
+    if adler32(&output) != checksum {
+        return Err("zlib checksum mismatch");
+    }
+    Ok(output)
+}
+
+pub(super) fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+pub(crate) fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, &'static str> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        bit: 0,
+    };
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let header = data
+                    .get(reader.position..reader.position + 4)
+                    .ok_or("unexpected end of data")?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let inverse = u16::from_le_bytes([header[2], header[3]]) as usize;
+                if length != !inverse & 0xffff {
+                    return Err("invalid stored block length");
+                }
+                reader.position += 4;
+                let stored = data
+                    .get(reader.position..reader.position + length)
+                    .ok_or("unexpected end of data")?;
+                if output.len() + length > limit {
+                    return Err("inflated data is too large");
+                }
+                output.extend_from_slice(stored);
+                reader.position += length;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut reader, &mut output, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &mut output, limit, &literals, &distances)?;
+            }
+            _ => return Err("invalid deflate block type"),
+        }
+        if output.len() > limit {
+            return Err("inflated data is too large");
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn read_dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), &'static str> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_length_huffman = Huffman::new(&code_lengths);
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_length_huffman.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..index]
+                    .last()
+                    .ok_or("repeat without a previous length")?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if index + repeat > lengths.len() {
+            return Err("too many code lengths");
+        }
+        lengths[index..index + repeat].fill(value);
+        index += repeat;
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), &'static str> {
+    loop {
+        if output.len() > limit {
+            return Err("inflated data is too large");
+        }
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length =
+                    LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err("invalid distance symbol");
+                }
+                let distance = DISTANCE_BASE[index] as usize
+                    + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > output.len() {
+                    return Err("distance is too far back");
+                }
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+            _ => return Err("invalid literal symbol"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXED: [u8; 21] = [
+        120, 218, 203, 72, 205, 201, 201, 87, 200, 64, 34, 179, 243, 11, 178, 51, 1, 104, 62, 8,
+        187,
+    ];
+    const DYNAMIC: [u8; 81] = [
+        120, 218, 77, 140, 93, 10, 192, 32, 12, 131, 175, 146, 171, 117, 88, 183, 34, 171, 82, 43,
+        94, 127, 254, 61, 236, 33, 16, 146, 47, 33, 13, 160, 33, 81, 207, 136, 70, 47, 95, 45, 70,
+        54, 24, 107, 96, 171, 232, 119, 105, 72, 185, 36, 65, 23, 21, 71, 45, 38, 206, 163, 16,
+        127, 78, 180, 24, 58, 212, 255, 100, 189, 110, 102, 219, 9, 206, 225, 7, 120, 243, 44, 219,
+    ];
+    const DYNAMIC_TEXT: &[u8] = b"and and into framebuffer renders wgpu kopki winit sprites with \
+        winit wgpu a kopki framebuffer into winit into wgpu with";
+
+    #[test]
+    fn stored_block() {
+        let data = [1, 5, 0, 0xfa, 0xff, b'k', b'o', b'p', b'k', b'i'];
+        assert_eq!(inflate(&data, usize::MAX).unwrap(), b"kopki");
+    }
+
+    #[test]
+    fn fixed_block() {
+        assert_eq!(FIXED[2] >> 1 & 3, 1);
+        assert_eq!(
+            zlib_decompress(&FIXED, usize::MAX).unwrap(),
+            b"hello hello hello kopki"
+        );
+    }
+
+    #[test]
+    fn dynamic_block() {
+        assert_eq!(DYNAMIC[2] >> 1 & 3, 2);
+        assert_eq!(zlib_decompress(&DYNAMIC, usize::MAX).unwrap(), DYNAMIC_TEXT);
+    }
+
+    #[test]
+    fn bad_checksum() {
+        let mut data = FIXED.to_vec();
+        *data.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            zlib_decompress(&data, usize::MAX),
+            Err("zlib checksum mismatch")
+        );
+    }
+
+    #[test]
+    fn bad_header() {
+        let mut data = FIXED.to_vec();
+        data[1] = 0;
+        assert!(zlib_decompress(&data, usize::MAX).is_err());
+        data[0] = 0x77;
+        assert!(zlib_decompress(&data, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn truncated_streams() {
+        for data in [&FIXED[..], &DYNAMIC[..]] {
+            for length in 0..data.len() {
+                assert!(zlib_decompress(&data[..length], usize::MAX).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_blocks() {
+        assert_eq!(
+            inflate(&[1, 5, 0, 0, 0, 0, 0, 0, 0, 0], usize::MAX),
+            Err("invalid stored block length")
+        );
+        assert_eq!(inflate(&[7], usize::MAX), Err("invalid deflate block type"));
+    }
+
+    #[test]
+    fn output_limit() {
+        assert_eq!(
+            zlib_decompress(&DYNAMIC, 16),
+            Err("inflated data is too large")
+        );
+        assert_eq!(
+            inflate(&[1, 5, 0, 0xfa, 0xff, b'k', b'o', b'p', b'k', b'i'], 4),
+            Err("inflated data is too large")
+        );
+    }
+}
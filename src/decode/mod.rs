@@ -0,0 +1,55 @@
+#[cfg(not(feature = "image"))]
+mod inflate;
+#[cfg(not(feature = "image"))]
+mod png;
+#[cfg(not(feature = "image"))]
+mod qoi;
+
+use std::path::Path;
+
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Image> {
+        #[cfg(feature = "image")]
+        {
+            let image = ::image::load_from_memory(bytes)
+                .map_err(|error| Error::DecodeImage(error.to_string()))?
+                .to_rgba8();
+            Ok(Image {
+                width: image.width(),
+                height: image.height(),
+                pixels: image.into_raw(),
+            })
+        }
+        #[cfg(not(feature = "image"))]
+        {
+            let decoded = if bytes.starts_with(&png::SIGNATURE) {
+                png::decode(bytes)
+            } else if bytes.starts_with(qoi::MAGIC) {
+                qoi::decode(bytes)
+            } else {
+                Err("unsupported image format, enable the `image` feature for more formats")
+            };
+            decoded.map_err(|error| Error::DecodeImage(error.to_string()))
+        }
+    }
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Image> {
+        Image::from_bytes(&std::fs::read(path)?)
+    }
+    pub fn premultiply_alpha(&mut self) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u16;
+            for channel in &mut pixel[..3] {
+                *channel = ((*channel as u16 * alpha + 127) / 255) as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use super::inflate::zlib_decompress;
+use super::Image;
+
+pub(crate) const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+const MAX_DIMENSION: u32 = i32::MAX as u32;
+
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image, &'static str> {
+    if !bytes.starts_with(&SIGNATURE) {
+        return Err("not a png file");
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut position = SIGNATURE.len();
+    while position + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[position..position + 4].try_into().unwrap()) as usize;
+        let kind = &bytes[position + 4..position + 8];
+        let data = (position + 8)
+            .checked_add(length)
+            .and_then(|end| bytes.get(position + 8..end))
+            .ok_or("truncated png chunk")?;
+        position += 12 + length;
+
+        match kind {
+            b"IHDR" => {
+                if data.len() < 13 {
+                    return Err("invalid png header");
+                }
+                if data[12] != 0 {
+                    return Err("interlaced png files are not supported");
+                }
+                header = Some(Header {
+                    width: u32::from_be_bytes(data[0..4].try_into().unwrap()),
+                    height: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+                    bit_depth: data[8],
+                    color_type: data[9],
+                });
+            }
+            b"PLTE" => palette = data,
+            b"tRNS" => transparency = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => (),
+        }
+    }
+
+    let header = header.ok_or("missing png header")?;
+    let (channels, depths): (usize, &[u8]) = match header.color_type {
+        0 => (1, &[1, 2, 4, 8, 16]),
+        2 => (3, &[8, 16]),
+        3 => (1, &[1, 2, 4, 8]),
+        4 => (2, &[8, 16]),
+        6 => (4, &[8, 16]),
+        _ => return Err("invalid png color type"),
+    };
+    if !depths.contains(&header.bit_depth) {
+        return Err("invalid png bit depth for the color type");
+    }
+    if header.width == 0
+        || header.height == 0
+        || header.width > MAX_DIMENSION
+        || header.height > MAX_DIMENSION
+    {
+        return Err("invalid png dimensions");
+    }
+    let bits_per_pixel = channels * header.bit_depth as usize;
+    let stride = (header.width as usize)
+        .checked_mul(bits_per_pixel)
+        .ok_or("png image is too large")?
+        .div_ceil(8);
+    let bytes_per_pixel = (bits_per_pixel / 8).max(1);
+    let filtered_len = (stride + 1)
+        .checked_mul(header.height as usize)
+        .ok_or("png image is too large")?;
+    let pixels_len = (header.width as usize)
+        .checked_mul(header.height as usize)
+        .and_then(|count| count.checked_mul(4))
+        .ok_or("png image is too large")?;
+
+    let filtered = zlib_decompress(&compressed, filtered_len)?;
+    if filtered.len() < filtered_len {
+        return Err("png image data is too short");
+    }
+    let scanlines = unfilter(&filtered, stride, bytes_per_pixel, header.height as usize)?;
+
+    let depth = header.bit_depth;
+    let mut pixels = Vec::with_capacity(pixels_len);
+    for row in scanlines.chunks_exact(stride) {
+        for x in 0..header.width as usize {
+            let sample = |channel: usize| read_sample(row, x * channels + channel, depth);
+            let rgba = match header.color_type {
+                0 => {
+                    let gray = sample(0);
+                    let value = scale_sample(gray, depth);
+                    [value, value, value, color_key_alpha(transparency, &[gray])]
+                }
+                2 => {
+                    let (r, g, b) = (sample(0), sample(1), sample(2));
+                    [
+                        scale_sample(r, depth),
+                        scale_sample(g, depth),
+                        scale_sample(b, depth),
+                        color_key_alpha(transparency, &[r, g, b]),
+                    ]
+                }
+                3 => {
+                    let index = sample(0) as usize;
+                    let color = palette
+                        .get(index * 3..index * 3 + 3)
+                        .ok_or("png palette index out of range")?;
+                    let alpha = transparency.get(index).copied().unwrap_or(255);
+                    [color[0], color[1], color[2], alpha]
+                }
+                4 => {
+                    let value = scale_sample(sample(0), depth);
+                    [value, value, value, scale_sample(sample(1), depth)]
+                }
+                _ => [
+                    scale_sample(sample(0), depth),
+                    scale_sample(sample(1), depth),
+                    scale_sample(sample(2), depth),
+                    scale_sample(sample(3), depth),
+                ],
+            };
+            pixels.extend_from_slice(&rgba);
+        }
+    }
+
+    Ok(Image {
+        width: header.width,
+        height: header.height,
+        pixels,
+    })
+}
+
+fn unfilter(
+    data: &[u8],
+    stride: usize,
+    bytes_per_pixel: usize,
+    height: usize,
+) -> Result<Vec<u8>, &'static str> {
+    let mut output = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = data[y * (stride + 1)];
+        let line = &data[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (previous, current) = output.split_at_mut(y * stride);
+        let previous = if y == 0 {
+            None
+        } else {
+            Some(&previous[(y - 1) * stride..])
+        };
+        let current = &mut current[..stride];
+
+        for x in 0..stride {
+            let left = if x >= bytes_per_pixel {
+                current[x - bytes_per_pixel]
+            } else {
+                0
+            };
+            let up = previous.map_or(0, |previous| previous[x]);
+            let up_left = match previous {
+                Some(previous) if x >= bytes_per_pixel => previous[x - bytes_per_pixel],
+                _ => 0,
+            };
+            current[x] = match filter {
+                0 => line[x],
+                1 => line[x].wrapping_add(left),
+                2 => line[x].wrapping_add(up),
+                3 => line[x].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                4 => line[x].wrapping_add(paeth(left, up, up_left)),
+                _ => return Err("invalid png filter type"),
+            };
+        }
+    }
+    Ok(output)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn read_sample(row: &[u8], index: usize, bit_depth: u8) -> u16 {
+    match bit_depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * bit_depth as usize;
+            let shift = 8 - bit_depth as usize - bit % 8;
+            ((row[bit / 8] >> shift) & ((1 << bit_depth) - 1)) as u16
+        }
+    }
+}
+
+fn scale_sample(value: u16, bit_depth: u8) -> u8 {
+    match bit_depth {
+        16 => (value >> 8) as u8,
+        8 => value as u8,
+        _ => (value * 255 / ((1 << bit_depth) - 1)) as u8,
+    }
+}
+
+fn color_key_alpha(transparency: &[u8], samples: &[u16]) -> u8 {
+    if transparency.len() < samples.len() * 2 {
+        return 255;
+    }
+    let matches = samples.iter().enumerate().all(|(i, &sample)| {
+        u16::from_be_bytes([transparency[i * 2], transparency[i * 2 + 1]]) == sample
+    });
+    if matches {
+        0
+    } else {
+        255
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::inflate::adler32;
+    use super::*;
+
+    fn chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = (data.len() as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(&[0; 4]);
+        bytes
+    }
+
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let length = data.len() as u16;
+        let mut bytes = vec![0x78, 0x01, 1];
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&(!length).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(&adler32(data).to_be_bytes());
+        bytes
+    }
+
+    fn png(width: u32, height: u32, bit_depth: u8, color_type: u8, chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut header = width.to_be_bytes().to_vec();
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend(chunk(b"IHDR", &header));
+        for extra in chunks {
+            bytes.extend_from_slice(extra);
+        }
+        bytes.extend(chunk(b"IEND", &[]));
+        bytes
+    }
+
+    fn idat(scanlines: &[u8]) -> Vec<u8> {
+        chunk(b"IDAT", &zlib_stored(scanlines))
+    }
+
+    #[test]
+    fn decodes_test_image() {
+        let image = decode(include_bytes!("../../resources/test.png")).unwrap();
+        assert_eq!((image.width, image.height), (256, 256));
+        assert_eq!(image.pixels.len(), 256 * 256 * 4);
+        assert_eq!(image.pixels[..4], [0, 0, 0, 0]);
+        let index = (26 * 256 + 128) * 4;
+        assert_eq!(image.pixels[index..index + 4], [0, 0, 0, 255]);
+        let total = image.pixels.iter().map(|&byte| byte as u32).sum::<u32>();
+        assert_eq!(total, 949_620);
+    }
+
+    #[test]
+    fn decodes_filtered_rgb() {
+        let scanlines = [
+            0, 10, 20, 30, 40, 50, 60, 2, 1, 1, 1, 2, 2, 2, 4, 1, 1, 1, 1, 1, 1,
+        ];
+        let image = decode(&png(2, 3, 8, 2, &[idat(&scanlines)])).unwrap();
+        assert_eq!(
+            image.pixels,
+            [
+                10, 20, 30, 255, 40, 50, 60, 255, 11, 21, 31, 255, 42, 52, 62, 255, 12, 22, 32,
+                255, 43, 53, 63, 255,
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_low_bit_depths() {
+        let image = decode(&png(8, 1, 1, 0, &[idat(&[0, 0b1010_0000])])).unwrap();
+        let gray = image
+            .pixels
+            .chunks(4)
+            .map(|pixel| pixel[0])
+            .collect::<Vec<_>>();
+        assert_eq!(gray, [255, 0, 255, 0, 0, 0, 0, 0]);
+
+        let palette = chunk(b"PLTE", &[255, 0, 0, 0, 0, 255]);
+        let transparency = chunk(b"tRNS", &[128]);
+        let image = decode(&png(
+            2,
+            1,
+            2,
+            3,
+            &[palette, transparency, idat(&[0, 0b0001_0000])],
+        ))
+        .unwrap();
+        assert_eq!(image.pixels, [255, 0, 0, 128, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn decodes_sixteen_bit_gray_alpha() {
+        let image = decode(&png(1, 1, 16, 4, &[idat(&[0, 0x80, 0x00, 0xff, 0xff])])).unwrap();
+        assert_eq!(image.pixels, [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn rejects_invalid_bit_depths() {
+        for (bit_depth, color_type) in [(0, 0), (3, 0), (32, 0), (4, 2), (1, 6), (16, 3), (8, 5)] {
+            let bytes = png(
+                1,
+                1,
+                bit_depth,
+                color_type,
+                &[idat(&[0, 0, 0, 0, 0, 0, 0, 0, 0])],
+            );
+            assert!(decode(&bytes).is_err(), "{} {}", bit_depth, color_type);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dimensions() {
+        for (width, height) in [(0, 1), (1, 0), (u32::MAX, 1), (0x7fff_ffff, 0x7fff_ffff)] {
+            let bytes = png(width, height, 8, 6, &[idat(&[0, 0, 0, 0, 0])]);
+            assert!(decode(&bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert_eq!(decode(b"not a png"), Err("not a png file"));
+        assert_eq!(decode(&SIGNATURE), Err("missing png header"));
+
+        let mut header = SIGNATURE.to_vec();
+        header.extend(chunk(b"IHDR", &[0, 0, 0, 1]));
+        assert_eq!(decode(&header), Err("invalid png header"));
+
+        let mut corrupted = png(1, 1, 8, 0, &[idat(&[0, 7])]);
+        let checksum = corrupted.len() - 17;
+        corrupted[checksum] ^= 1;
+        assert_eq!(decode(&corrupted), Err("zlib checksum mismatch"));
+
+        let short = png(2, 2, 8, 0, &[idat(&[0, 7, 7])]);
+        assert_eq!(decode(&short), Err("png image data is too short"));
+
+        let bad_filter = png(1, 1, 8, 0, &[idat(&[9, 7])]);
+        assert_eq!(decode(&bad_filter), Err("invalid png filter type"));
+    }
+
+    #[test]
+    fn truncated_files_return_errors() {
+        let bytes = include_bytes!("../../resources/test.png");
+        for length in (0..bytes.len()).step_by(37) {
+            assert!(decode(&bytes[..length]).is_err());
+        }
+    }
+}
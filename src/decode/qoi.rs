@@ -0,0 +1,149 @@
+use super::Image;
+
+pub(crate) const MAGIC: &[u8; 4] = b"qoif";
+
+const MAX_PIXELS: usize = 400_000_000;
+const MAX_RUN: usize = 62;
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image, &'static str> {
+    if bytes.len() < 14 || !bytes.starts_with(MAGIC) {
+        return Err("not a qoi file");
+    }
+    let width = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+    let height = u32::from_be_bytes(bytes[8..12].try_into().unwrap());
+    let pixel_count = (width as usize)
+        .checked_mul(height as usize)
+        .filter(|&count| count <= MAX_PIXELS && count <= (bytes.len() - 14) * MAX_RUN)
+        .ok_or("qoi image is too large")?;
+
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+    let mut index = [[0u8; 4]; 64];
+    let mut pixel = [0u8, 0, 0, 255];
+    let mut position = 14;
+    let mut run = 0;
+
+    let byte = |position: usize| bytes.get(position).copied().ok_or("truncated qoi data");
+    while pixels.len() < pixel_count * 4 {
+        if run > 0 {
+            run -= 1;
+        } else {
+            let tag = byte(position)?;
+            position += 1;
+            match tag {
+                0xfe => {
+                    pixel[0] = byte(position)?;
+                    pixel[1] = byte(position + 1)?;
+                    pixel[2] = byte(position + 2)?;
+                    position += 3;
+                }
+                0xff => {
+                    pixel[0] = byte(position)?;
+                    pixel[1] = byte(position + 1)?;
+                    pixel[2] = byte(position + 2)?;
+                    pixel[3] = byte(position + 3)?;
+                    position += 4;
+                }
+                _ => match tag >> 6 {
+                    0 => pixel = index[(tag & 0x3f) as usize],
+                    1 => {
+                        pixel[0] = pixel[0].wrapping_add((tag >> 4) & 3).wrapping_sub(2);
+                        pixel[1] = pixel[1].wrapping_add((tag >> 2) & 3).wrapping_sub(2);
+                        pixel[2] = pixel[2].wrapping_add(tag & 3).wrapping_sub(2);
+                    }
+                    2 => {
+                        let next = byte(position)?;
+                        position += 1;
+                        let green = (tag & 0x3f).wrapping_sub(32);
+                        pixel[0] = pixel[0]
+                            .wrapping_add(green)
+                            .wrapping_add(next >> 4)
+                            .wrapping_sub(8);
+                        pixel[1] = pixel[1].wrapping_add(green);
+                        pixel[2] = pixel[2]
+                            .wrapping_add(green)
+                            .wrapping_add(next & 0x0f)
+                            .wrapping_sub(8);
+                    }
+                    _ => run = tag & 0x3f,
+                },
+            }
+            let hash = pixel[0] as usize * 3
+                + pixel[1] as usize * 5
+                + pixel[2] as usize * 7
+                + pixel[3] as usize * 11;
+            index[hash % 64] = pixel;
+        }
+        pixels.extend_from_slice(&pixel);
+    }
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qoi(width: u32, height: u32, ops: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&[4, 0]);
+        bytes.extend_from_slice(ops);
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        bytes
+    }
+
+    const OPS: [u8; 16] = [
+        0xfe, 10, 20, 30, 0x79, 0x09, 0xa2, 0x89, 0xc1, 0xff, 1, 2, 3, 4, 0xc0, 0x09,
+    ];
+
+    #[test]
+    fn decodes_every_op() {
+        let image = decode(&qoi(3, 3, &OPS)).unwrap();
+        assert_eq!((image.width, image.height), (3, 3));
+        let pixels = image.pixels.chunks(4).collect::<Vec<_>>();
+        assert_eq!(
+            pixels,
+            [
+                [10, 20, 30, 255],
+                [11, 20, 29, 255],
+                [10, 20, 30, 255],
+                [12, 22, 33, 255],
+                [12, 22, 33, 255],
+                [12, 22, 33, 255],
+                [1, 2, 3, 4],
+                [1, 2, 3, 4],
+                [10, 20, 30, 255],
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = qoi(3, 3, &OPS);
+        for length in 0..14 + OPS.len() - 1 {
+            assert!(decode(&bytes[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_images() {
+        assert_eq!(
+            decode(&qoi(u32::MAX, u32::MAX, &[])),
+            Err("qoi image is too large")
+        );
+        assert_eq!(
+            decode(&qoi(100_000, 100_000, &[0xfd; 16])),
+            Err("qoi image is too large")
+        );
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        assert_eq!(decode(b"qoix0000000000"), Err("not a qoi file"));
+    }
+}
@@ -0,0 +1,81 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StencilMode {
+    #[default]
+    Disabled,
+    Write,
+    Inside,
+    Outside,
+}
+
+impl StencilMode {
+    pub(crate) fn color_writes(self) -> wgpu::ColorWrites {
+        match self {
+            StencilMode::Write => wgpu::ColorWrites::empty(),
+            _ => wgpu::ColorWrites::ALL,
+        }
+    }
+    pub(crate) fn depth_stencil_state(
+        self,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Option<wgpu::DepthStencilState> {
+        let format = depth_format?;
+        let face = match self {
+            StencilMode::Disabled => wgpu::StencilFaceState::IGNORE,
+            StencilMode::Write => wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Always,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op: wgpu::StencilOperation::Replace,
+            },
+            StencilMode::Inside => wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Equal,
+                ..wgpu::StencilFaceState::IGNORE
+            },
+            StencilMode::Outside => wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::NotEqual,
+                ..wgpu::StencilFaceState::IGNORE
+            },
+        };
+        let stencil = if format.has_stencil_aspect() {
+            wgpu::StencilState {
+                front: face,
+                back: face,
+                read_mask: !0,
+                write_mask: !0,
+            }
+        } else {
+            wgpu::StencilState::default()
+        };
+
+        Some(wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: format.has_depth_aspect() && self != StencilMode::Write,
+            depth_compare: if format.has_depth_aspect() {
+                wgpu::CompareFunction::LessEqual
+            } else {
+                wgpu::CompareFunction::Always
+            },
+            stencil,
+            bias: wgpu::DepthBiasState::default(),
+        })
+    }
+}
+
+pub(crate) fn depth_stencil_attachment(
+    view: &wgpu::TextureView,
+    format: wgpu::TextureFormat,
+    depth: wgpu::LoadOp<f32>,
+    stencil: wgpu::LoadOp<u32>,
+) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+    wgpu::RenderPassDepthStencilAttachment {
+        view,
+        depth_ops: format.has_depth_aspect().then_some(wgpu::Operations {
+            load: depth,
+            store: wgpu::StoreOp::Store,
+        }),
+        stencil_ops: format.has_stencil_aspect().then_some(wgpu::Operations {
+            load: stencil,
+            store: wgpu::StoreOp::Store,
+        }),
+    }
+}
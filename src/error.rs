@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::shader::ShaderDiagnostic;
+
+#[derive(Debug)]
+pub enum Error {
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    CreateSurface(wgpu::CreateSurfaceError),
+    CreateWindow(winit::error::OsError),
+    EventLoop(winit::error::EventLoopError),
+    UnsupportedSurface,
+    UnsupportedPresentMode(wgpu::PresentMode),
+    UnsupportedSampleCount(u32),
+    UnsupportedFormat(wgpu::TextureFormat),
+    MissingUsage(wgpu::TextureUsages),
+    SurfaceLost,
+    SurfaceOutdated,
+    SurfaceTimeout,
+    DeviceLost,
+    OutOfMemory,
+    BufferMap(wgpu::BufferAsyncError),
+    RegionOutOfBounds,
+    InvalidPixelData { width: u32, height: u32, len: usize },
+    InvalidTextureSize { width: u32, height: u32 },
+    AtlasTooLarge,
+    Io(std::io::Error),
+    DecodeImage(String),
+    InvalidFont,
+    ParseObj { line: usize, message: String },
+    ShaderCompilation(Vec<ShaderDiagnostic>),
+    ShaderValidation(String),
+    ParamsTooLarge { size: usize, limit: u64 },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoAdapter => write!(f, "no suitable graphics adapter was found"),
+            Error::RequestDevice(error) => write!(f, "failed to request device: {}", error),
+            Error::CreateSurface(error) => write!(f, "failed to create surface: {}", error),
+            Error::CreateWindow(error) => write!(f, "failed to create window: {}", error),
+            Error::EventLoop(error) => write!(f, "event loop error: {}", error),
+            Error::UnsupportedSurface => write!(f, "the surface is not supported by the adapter"),
+            Error::UnsupportedPresentMode(mode) => {
+                write!(f, "the present mode {:?} is not supported", mode)
+            }
+            Error::UnsupportedSampleCount(count) => {
+                write!(f, "a sample count of {} is not supported", count)
+            }
+            Error::UnsupportedFormat(format) => {
+                write!(f, "the texture format {:?} is not supported here", format)
+            }
+            Error::MissingUsage(usage) => {
+                write!(f, "the texture was not created with the {:?} usage", usage)
+            }
+            Error::SurfaceLost => write!(f, "the surface was lost"),
+            Error::SurfaceOutdated => write!(f, "the surface is outdated"),
+            Error::SurfaceTimeout => write!(f, "timed out acquiring the surface texture"),
+            Error::DeviceLost => write!(f, "the device was lost"),
+            Error::OutOfMemory => write!(f, "the device ran out of memory"),
+            Error::BufferMap(error) => write!(f, "failed to map buffer: {}", error),
+            Error::RegionOutOfBounds => write!(f, "the region is out of the texture bounds"),
+            Error::InvalidPixelData { width, height, len } => write!(
+                f,
+                "{} bytes of pixel data do not match a {}x{} rgba8 image",
+                len, width, height
+            ),
+            Error::InvalidTextureSize { width, height } => {
+                write!(f, "a {}x{} texture is not a valid size", width, height)
+            }
+            Error::Io(error) => write!(f, "io error: {}", error),
+            Error::DecodeImage(error) => write!(f, "failed to decode image: {}", error),
+            Error::InvalidFont => write!(f, "the font data could not be parsed"),
+            Error::ParseObj { line, message } => {
+                write!(f, "failed to parse obj at line {}: {}", line, message)
+            }
+            Error::ShaderCompilation(diagnostics) => {
+                write!(f, "failed to compile shader")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            Error::ShaderValidation(error) => write!(f, "invalid shader pipeline: {}", error),
+            Error::ParamsTooLarge { size, limit } => {
+                write!(
+                    f,
+                    "{} bytes of parameters do not fit in the {} byte effect buffer",
+                    size, limit
+                )
+            }
+            Error::AtlasTooLarge => write!(f, "the atlas images do not fit in the maximum size"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::RequestDevice(error) => Some(error),
+            Error::CreateSurface(error) => Some(error),
+            Error::CreateWindow(error) => Some(error),
+            Error::EventLoop(error) => Some(error),
+            Error::BufferMap(error) => Some(error),
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error {
+    fn from(error: wgpu::RequestDeviceError) -> Self {
+        Error::RequestDevice(error)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for Error {
+    fn from(error: wgpu::CreateSurfaceError) -> Self {
+        Error::CreateSurface(error)
+    }
+}
+
+impl From<winit::error::OsError> for Error {
+    fn from(error: winit::error::OsError) -> Self {
+        Error::CreateWindow(error)
+    }
+}
+
+impl From<winit::error::EventLoopError> for Error {
+    fn from(error: winit::error::EventLoopError) -> Self {
+        Error::EventLoop(error)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for Error {
+    fn from(error: wgpu::BufferAsyncError) -> Self {
+        Error::BufferMap(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(error: wgpu::SurfaceError) -> Self {
+        match error {
+            wgpu::SurfaceError::Lost => Error::SurfaceLost,
+            wgpu::SurfaceError::Outdated => Error::SurfaceOutdated,
+            wgpu::SurfaceError::Timeout => Error::SurfaceTimeout,
+            wgpu::SurfaceError::OutOfMemory => Error::OutOfMemory,
+        }
+    }
+}
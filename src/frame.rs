@@ -0,0 +1,150 @@
+use crate::framebuffer::FrameBuffer;
+use crate::mesh::MeshRenderer;
+use crate::shapes::ShapeRenderer;
+use crate::sprite::SpriteBatch;
+use crate::text::TextRenderer;
+use crate::texture::RenderableTexture;
+use crate::RenderSurface;
+
+pub trait Renderer {
+    fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    );
+}
+
+pub struct Frame<'a> {
+    framebuffer: &'a FrameBuffer,
+    surface: &'a RenderSurface<'a>,
+    output: Option<wgpu::SurfaceTexture>,
+    encoder: Option<wgpu::CommandEncoder>,
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn new(
+        framebuffer: &'a FrameBuffer,
+        surface: &'a RenderSurface<'a>,
+        output: wgpu::SurfaceTexture,
+        encoder: wgpu::CommandEncoder,
+    ) -> Frame<'a> {
+        Frame {
+            framebuffer,
+            surface,
+            output: Some(output),
+            encoder: Some(encoder),
+        }
+    }
+    pub const fn framebuffer(&self) -> &'a FrameBuffer {
+        self.framebuffer
+    }
+    pub const fn surface(&self) -> &'a RenderSurface<'a> {
+        self.surface
+    }
+    pub fn target(&self) -> &'a RenderableTexture {
+        self.framebuffer.renderable_texture()
+    }
+    pub fn size(&self) -> (u32, u32) {
+        self.framebuffer.size()
+    }
+    pub fn encoder(&mut self) -> &mut wgpu::CommandEncoder {
+        self.encoder.as_mut().unwrap()
+    }
+    pub fn clear(&mut self, color: wgpu::Color) {
+        self.clear_all(color, 1.0, 0);
+    }
+    pub fn clear_all(&mut self, color: wgpu::Color, depth: f32, stencil: u32) {
+        let target = self.target();
+        target.clear_all_with_encoder(self.encoder(), color, depth, stencil);
+    }
+    pub fn draw(&mut self, renderer: &mut impl Renderer) {
+        let target = self.target();
+        renderer.flush_with_encoder(self.encoder(), target);
+    }
+    pub fn clear_layer(&mut self, index: usize, color: wgpu::Color) {
+        let target = self.framebuffer.layer(index).target();
+        target.clear_all_with_encoder(self.encoder(), color, 1.0, 0);
+    }
+    pub fn draw_layer(&mut self, index: usize, renderer: &mut impl Renderer) {
+        let target = self.framebuffer.layer(index).target();
+        renderer.flush_with_encoder(self.encoder(), target);
+    }
+    pub fn begin_pass(&mut self, load: wgpu::LoadOp<wgpu::Color>) -> wgpu::RenderPass<'_> {
+        let target = self.target();
+        let view = target.create_view();
+        let multisampled_view = target.create_multisampled_view();
+        let depth_view = target.create_depth_view();
+        self.encoder()
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Frame Render Pass"),
+                color_attachments: &[Some(target.color_attachment(
+                    &view,
+                    multisampled_view.as_ref(),
+                    load,
+                ))],
+                depth_stencil_attachment: target.depth_attachment(depth_view.as_ref()),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+    }
+    pub fn finish(mut self) {
+        self.present();
+    }
+    pub fn cancel(mut self) {
+        self.encoder = None;
+        self.output = None;
+    }
+    fn present(&mut self) {
+        if let (Some(encoder), Some(output)) = (self.encoder.take(), self.output.take()) {
+            self.framebuffer.present_to(self.surface, encoder, output);
+        }
+    }
+}
+
+impl<'a> Drop for Frame<'a> {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.present();
+        }
+    }
+}
+
+impl Renderer for ShapeRenderer {
+    fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        ShapeRenderer::flush_with_encoder(self, encoder, target);
+    }
+}
+
+impl Renderer for SpriteBatch {
+    fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        SpriteBatch::flush_with_encoder(self, encoder, target);
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        TextRenderer::flush_with_encoder(self, encoder, target);
+    }
+}
+
+impl Renderer for MeshRenderer {
+    fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        MeshRenderer::flush_with_encoder(self, encoder, target);
+    }
+}
@@ -0,0 +1,706 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
+
+use bytemuck::Zeroable;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::frame::Frame;
+use crate::layer::{BlendMode, Layer, LayerCompositor};
+use crate::pixels::PixelBuffer;
+use crate::postprocess::PostProcessChain;
+use crate::scaling::{ScalingMode, Viewport};
+use crate::shader::{create_shader_module, validate, ShaderSource};
+use crate::target::RenderTarget;
+use crate::texture::{
+    create_texture_bind_group, create_texture_bind_group_layout, RenderableTexture,
+    TextureBindGroupCache, TextureSampler,
+};
+use crate::{color_to_array, ArcedRenderDevice, Error, RenderDevice, RenderSurface, Result};
+
+const PRESENT_PRELUDE: &str = include_str!("shaders/present_prelude.wgsl");
+const PRESENT_SHADER: &str = include_str!("shaders/present.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Global {
+    framebuffer_size: [f32; 2],
+    surface_size: [f32; 2],
+    viewport_offset: [f32; 2],
+    viewport_size: [f32; 2],
+    bar_color: [f32; 4],
+    mouse_position: [f32; 2],
+    time: f32,
+    frame: u32,
+}
+
+pub struct FrameBuffer {
+    device: ArcedRenderDevice,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    texture: RenderableTexture,
+    sampler: TextureSampler,
+    pipeline_layout: wgpu::PipelineLayout,
+    present_source: ShaderSource,
+    pipeline: wgpu::RenderPipeline,
+    texture_bind_group: wgpu::BindGroup,
+    output_bind_group: TextureBindGroupCache,
+    global_bind_group_layout: wgpu::BindGroupLayout,
+    global_buffer: wgpu::Buffer,
+    user_buffer: wgpu::Buffer,
+    global_bind_group: wgpu::BindGroup,
+    format: wgpu::TextureFormat,
+    fixed_size: bool,
+    scaling_mode: ScalingMode,
+    bar_color: wgpu::Color,
+    pixels: Option<PixelBuffer>,
+    mipmaps: bool,
+    sample_count: u32,
+    depth_format: Option<wgpu::TextureFormat>,
+    layers: Vec<Layer>,
+    compositor: Option<LayerCompositor>,
+    post_process: PostProcessChain,
+    start: Instant,
+    frame: AtomicU32,
+    mouse_position: [f32; 2],
+}
+
+impl FrameBuffer {
+    pub fn new(device: &ArcedRenderDevice, surface: &RenderSurface) -> FrameBuffer {
+        Self::new_offscreen(
+            device,
+            surface.configuration.width,
+            surface.configuration.height,
+            surface.format,
+        )
+    }
+    pub fn new_offscreen(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> FrameBuffer {
+        Self::from_parts(device, width, height, format, false, ScalingMode::Stretch)
+    }
+    pub fn with_fixed_size(
+        device: &ArcedRenderDevice,
+        surface: &RenderSurface,
+        width: u32,
+        height: u32,
+        scaling_mode: ScalingMode,
+    ) -> FrameBuffer {
+        Self::from_parts(device, width, height, surface.format, true, scaling_mode)
+    }
+    fn from_parts(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        fixed_size: bool,
+        scaling_mode: ScalingMode,
+    ) -> FrameBuffer {
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let global_bind_group_layout =
+            device
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Global Bind Group Layout"),
+                    entries: &[uniform_entry(0), uniform_entry(1)],
+                });
+        let texture_bind_group_layout = create_texture_bind_group_layout(device);
+        let sampler = TextureSampler::new(device);
+        let texture = RenderableTexture::new(device, width, height, format);
+        let global_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Global Uniform Buffer"),
+            contents: bytemuck::bytes_of(&Global::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let user_buffer = create_user_buffer(device, &[0; 16]);
+        let global_bind_group = create_global_bind_group(
+            device,
+            &global_bind_group_layout,
+            &global_buffer,
+            &user_buffer,
+        );
+        let texture_bind_group = create_texture_bind_group(
+            device,
+            &texture_bind_group_layout,
+            &texture.create_sampled_view(),
+            &sampler,
+        );
+        let pipeline_layout =
+            device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("FrameBuffer Present Pipeline"),
+                    bind_group_layouts: &[&texture_bind_group_layout, &global_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+        let pipeline = create_present_pipeline(
+            device,
+            &pipeline_layout,
+            &create_default_present_shader(device),
+            format,
+        );
+
+        FrameBuffer {
+            device: device.clone(),
+            texture_bind_group_layout,
+            texture,
+            sampler,
+            pipeline_layout,
+            present_source: ShaderSource::embedded(PRESENT_SHADER),
+            pipeline,
+            texture_bind_group,
+            output_bind_group: TextureBindGroupCache::default(),
+            global_bind_group_layout,
+            global_buffer,
+            user_buffer,
+            global_bind_group,
+            format,
+            fixed_size,
+            scaling_mode,
+            bar_color: wgpu::Color::BLACK,
+            pixels: PixelBuffer::new(width, height, format).ok(),
+            mipmaps: false,
+            sample_count: 1,
+            depth_format: None,
+            layers: Vec::new(),
+            compositor: None,
+            post_process: PostProcessChain::new(device, width, height, format),
+            start: Instant::now(),
+            frame: AtomicU32::new(0),
+            mouse_position: [0.0, 0.0],
+        }
+    }
+    pub fn recreate(&self, device: &ArcedRenderDevice) -> Result<FrameBuffer> {
+        let (width, height) = self.size();
+        let mut framebuffer = Self::from_parts(
+            device,
+            width,
+            height,
+            self.format,
+            self.fixed_size,
+            self.scaling_mode,
+        );
+        if framebuffer
+            .set_present_shader(self.present_source.clone())
+            .is_err()
+        {
+            framebuffer.reset_present_shader();
+        }
+        framebuffer.set_mipmaps(self.mipmaps)?;
+        framebuffer.set_depth_format(self.depth_format);
+        framebuffer.set_sample_count(self.sample_count)?;
+        framebuffer.set_sampler(self.sampler.recreate(device));
+        framebuffer.set_bar_color(self.bar_color);
+        framebuffer.pixels.clone_from(&self.pixels);
+        framebuffer.layers = self
+            .layers
+            .iter()
+            .map(|layer| layer.recreate(device))
+            .collect::<Result<_>>()?;
+        framebuffer.compositor = self
+            .compositor
+            .as_ref()
+            .map(|_| LayerCompositor::new(device, width, height, self.format));
+        framebuffer.post_process = self.post_process.recreate(device);
+        framebuffer.start = self.start;
+        framebuffer.frame = AtomicU32::new(self.frame.load(Ordering::Relaxed));
+        framebuffer.mouse_position = self.mouse_position;
+        Ok(framebuffer)
+    }
+    pub fn set_present_shader(&mut self, source: impl Into<ShaderSource>) -> Result<()> {
+        let source = source.into();
+        self.pipeline = self.compile_present_shader(&source)?;
+        self.present_source = source;
+        Ok(())
+    }
+    pub fn reset_present_shader(&mut self) {
+        self.present_source = ShaderSource::embedded(PRESENT_SHADER);
+        self.pipeline = create_present_pipeline(
+            &self.device,
+            &self.pipeline_layout,
+            &create_default_present_shader(&self.device),
+            self.format,
+        );
+    }
+    pub const fn present_shader(&self) -> &ShaderSource {
+        &self.present_source
+    }
+    pub fn reload_shaders(&mut self) -> Result<bool> {
+        let present = match self.present_source.poll_changed() {
+            Ok(true) => self
+                .compile_present_shader(&self.present_source)
+                .map(|pipeline| {
+                    self.pipeline = pipeline;
+                    true
+                }),
+            result => result,
+        };
+        let post_process = self.post_process.reload();
+        Ok(present? | post_process?)
+    }
+    pub fn set_present_uniforms<T: bytemuck::Pod>(&mut self, uniforms: &T) {
+        let bytes = bytemuck::bytes_of(uniforms);
+        if bytes.len() as wgpu::BufferAddress > self.user_buffer.size() {
+            self.user_buffer = create_user_buffer(&self.device, bytes);
+            self.global_bind_group = create_global_bind_group(
+                &self.device,
+                &self.global_bind_group_layout,
+                &self.global_buffer,
+                &self.user_buffer,
+            );
+        } else {
+            self.device.queue.write_buffer(&self.user_buffer, 0, bytes);
+        }
+    }
+    pub fn set_mouse_position(&mut self, x: f64, y: f64) {
+        self.mouse_position = [x as f32, y as f32];
+    }
+    pub fn frame_index(&self) -> u32 {
+        self.frame.load(Ordering::Relaxed)
+    }
+    pub fn begin_frame<'a>(&'a self, surface: &'a RenderSurface) -> Result<Frame<'a>> {
+        if self.device.is_lost() {
+            return Err(Error::DeviceLost);
+        }
+        let output = surface.acquire(&self.device)?;
+        let encoder = self
+            .device
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Frame Command Encoder"),
+            });
+        Ok(Frame::new(self, surface, output, encoder))
+    }
+    pub fn present_with_encoder(
+        &self,
+        surface: &RenderSurface,
+        encoder: wgpu::CommandEncoder,
+    ) -> Result<()> {
+        if self.device.is_lost() {
+            return Err(Error::DeviceLost);
+        }
+        let output = surface.acquire(&self.device)?;
+        self.present_to(surface, encoder, output);
+        Ok(())
+    }
+    pub fn present(&self, surface: &RenderSurface) -> Result<()> {
+        self.begin_frame(surface)?.finish();
+        Ok(())
+    }
+    pub(crate) fn present_to(
+        &self,
+        surface: &RenderSurface,
+        mut encoder: wgpu::CommandEncoder,
+        output: wgpu::SurfaceTexture,
+    ) {
+        self.write_global(surface);
+        self.flush_pixels_with_encoder(&mut encoder);
+        if self.mipmaps {
+            self.texture.generate_mipmaps(&mut encoder);
+        }
+        let composited = self
+            .compositor
+            .as_ref()
+            .and_then(|compositor| compositor.run(&mut encoder, &self.texture, &self.layers));
+        let processed = self
+            .post_process
+            .run(&mut encoder, composited.unwrap_or(&self.texture))
+            .or(composited);
+        let surface_view = output.texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Surface Texture View"),
+            ..Default::default()
+        });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Present Framebuffer Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &surface_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            match processed {
+                Some(output) => self
+                    .output_bind_group
+                    .bind(&mut render_pass, 0, output, || {
+                        create_texture_bind_group(
+                            &self.device,
+                            &self.texture_bind_group_layout,
+                            &output.create_sampled_view(),
+                            &self.sampler,
+                        )
+                    }),
+                None => render_pass.set_bind_group(0, &self.texture_bind_group, &[]),
+            }
+            render_pass.set_bind_group(1, &self.global_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.device.queue.submit([encoder.finish()]);
+        output.present();
+        self.frame.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn rebuild(&mut self, surface: &RenderSurface) -> Result<()> {
+        if self.fixed_size {
+            return Ok(());
+        }
+        self.resize(surface.configuration.width, surface.configuration.height)
+    }
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.texture = if self.mipmaps {
+            RenderableTexture::new_with_mipmaps(&self.device, width, height, self.format)
+        } else {
+            RenderableTexture::new(&self.device, width, height, self.format)
+        };
+        self.texture.set_depth_format(self.depth_format);
+        self.texture.set_sample_count(self.sample_count)?;
+        self.pixels = PixelBuffer::new(width, height, self.format).ok();
+        for layer in &mut self.layers {
+            layer.target_mut().resize(width, height)?;
+        }
+        if let Some(compositor) = &mut self.compositor {
+            compositor.resize(width, height);
+        }
+        self.post_process.resize(width, height);
+        self.rebuild_texture_bind_group();
+        Ok(())
+    }
+    pub fn set_mipmaps(&mut self, mipmaps: bool) -> Result<()> {
+        if self.mipmaps == mipmaps {
+            return Ok(());
+        }
+        self.mipmaps = mipmaps;
+        let (width, height) = self.size();
+        self.resize(width, height)
+    }
+    pub const fn mipmaps(&self) -> bool {
+        self.mipmaps
+    }
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<()> {
+        self.texture.set_sample_count(sample_count)?;
+        self.sample_count = sample_count;
+        Ok(())
+    }
+    pub const fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+    pub fn set_depth_format(&mut self, depth_format: Option<wgpu::TextureFormat>) {
+        self.depth_format = depth_format;
+        self.texture.set_depth_format(depth_format);
+    }
+    pub const fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_format
+    }
+    pub fn frame(&self) -> Result<&[u8]> {
+        Ok(self.pixels()?.frame())
+    }
+    pub fn frame_mut(&mut self) -> Result<&mut [u8]> {
+        Ok(self.pixels_mut()?.frame_mut())
+    }
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        self.pixels.as_ref()?.get_pixel(x, y)
+    }
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) -> Result<()> {
+        self.pixels_mut()?.set_pixel(x, y, rgba);
+        Ok(())
+    }
+    pub fn pixels(&self) -> Result<&PixelBuffer> {
+        self.pixels
+            .as_ref()
+            .ok_or(Error::UnsupportedFormat(self.format))
+    }
+    pub fn pixels_mut(&mut self) -> Result<&mut PixelBuffer> {
+        self.pixels
+            .as_mut()
+            .ok_or(Error::UnsupportedFormat(self.format))
+    }
+    pub fn flush_pixels(&self) {
+        if !self.pixels.as_ref().is_some_and(PixelBuffer::is_dirty) {
+            return;
+        }
+        let mut encoder =
+            self.device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Pixel Upload Command Encoder"),
+                });
+        self.flush_pixels_with_encoder(&mut encoder);
+        self.device.queue.submit([encoder.finish()]);
+    }
+    pub fn flush_pixels_with_encoder(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(pixels) = &self.pixels {
+            pixels.record_upload(&self.device, encoder, &self.texture);
+        }
+    }
+    pub fn add_layer(&mut self, blend_mode: BlendMode) -> usize {
+        let (width, height) = self.size();
+        let target = RenderTarget::new(
+            &self.device,
+            width,
+            height,
+            self.format,
+            wgpu::TextureUsages::COPY_SRC,
+        );
+        self.compositor
+            .get_or_insert_with(|| LayerCompositor::new(&self.device, width, height, self.format));
+        self.layers
+            .push(Layer::new(&self.device, target, blend_mode));
+        self.layers.len() - 1
+    }
+    pub fn remove_layer(&mut self, index: usize) {
+        self.layers.remove(index);
+    }
+    pub fn clear_layers(&mut self) {
+        self.layers.clear();
+    }
+    pub fn layer(&self, index: usize) -> &Layer {
+        &self.layers[index]
+    }
+    pub fn layer_mut(&mut self, index: usize) -> &mut Layer {
+        &mut self.layers[index]
+    }
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+    pub const fn post_process(&self) -> &PostProcessChain {
+        &self.post_process
+    }
+    pub fn post_process_mut(&mut self) -> &mut PostProcessChain {
+        &mut self.post_process
+    }
+    pub fn set_sampler(&mut self, sampler: TextureSampler) {
+        self.sampler = sampler;
+        self.rebuild_texture_bind_group();
+    }
+    pub fn set_filter(&mut self, filter: wgpu::FilterMode) {
+        let sampler = TextureSampler::builder().filter(filter).build(&self.device);
+        self.set_sampler(sampler);
+    }
+    pub const fn sampler(&self) -> &TextureSampler {
+        &self.sampler
+    }
+    pub fn set_scaling_mode(&mut self, scaling_mode: ScalingMode) {
+        self.scaling_mode = scaling_mode;
+    }
+    pub const fn scaling_mode(&self) -> ScalingMode {
+        self.scaling_mode
+    }
+    pub fn set_bar_color(&mut self, bar_color: wgpu::Color) {
+        self.bar_color = bar_color;
+    }
+    pub fn viewport(&self, surface: &RenderSurface) -> Viewport {
+        self.scaling_mode.viewport(
+            self.size(),
+            (surface.configuration.width, surface.configuration.height),
+        )
+    }
+    pub fn window_to_framebuffer(
+        &self,
+        surface: &RenderSurface,
+        x: f64,
+        y: f64,
+    ) -> Option<(u32, u32)> {
+        self.viewport(surface)
+            .to_source(self.size(), x as f32, y as f32)
+    }
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+    pub const fn renderable_texture(&self) -> &RenderableTexture {
+        &self.texture
+    }
+    pub const fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+    fn compile_present_shader(&self, source: &ShaderSource) -> Result<wgpu::RenderPipeline> {
+        let shader = create_shader_module(
+            &self.device,
+            "Present Shader",
+            PRESENT_PRELUDE,
+            source.source(),
+        )?;
+        validate(&self.device, || {
+            create_present_pipeline(&self.device, &self.pipeline_layout, &shader, self.format)
+        })
+    }
+    fn rebuild_texture_bind_group(&mut self) {
+        self.output_bind_group.clear();
+        self.texture_bind_group = create_texture_bind_group(
+            &self.device,
+            &self.texture_bind_group_layout,
+            &self.texture.create_sampled_view(),
+            &self.sampler,
+        );
+    }
+    fn write_global(&self, surface: &RenderSurface) {
+        let (width, height) = self.size();
+        let viewport = self.viewport(surface);
+        let global = Global {
+            framebuffer_size: [width as f32, height as f32],
+            surface_size: [
+                surface.configuration.width as f32,
+                surface.configuration.height as f32,
+            ],
+            viewport_offset: [viewport.x, viewport.y],
+            viewport_size: [viewport.width, viewport.height],
+            bar_color: color_to_array(self.bar_color),
+            mouse_position: self.mouse_position,
+            time: self.start.elapsed().as_secs_f32(),
+            frame: self.frame.load(Ordering::Relaxed),
+        };
+        self.device
+            .queue
+            .write_buffer(&self.global_buffer, 0, bytemuck::bytes_of(&global));
+    }
+}
+
+fn create_user_buffer(device: &RenderDevice, contents: &[u8]) -> wgpu::Buffer {
+    let mut contents = contents.to_vec();
+    contents.resize(contents.len().next_multiple_of(16).max(16), 0);
+    device.device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("User Uniform Buffer"),
+        contents: &contents,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+fn create_global_bind_group(
+    device: &RenderDevice,
+    layout: &wgpu::BindGroupLayout,
+    global_buffer: &wgpu::Buffer,
+    user_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Global Uniform Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: global_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: user_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+fn create_default_present_shader(device: &RenderDevice) -> wgpu::ShaderModule {
+    device
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Present Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!("{}{}", PRESENT_PRELUDE, PRESENT_SHADER).into(),
+            ),
+        })
+}
+
+fn create_present_pipeline(
+    device: &RenderDevice,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postprocess::PostEffect;
+    use crate::RenderInstance;
+
+    #[test]
+    fn recreate_keeps_layers_and_effects() {
+        let instance = RenderInstance::new();
+        let Ok(device) = instance.headless_device() else {
+            return;
+        };
+        let mut framebuffer =
+            FrameBuffer::new_offscreen(&device, 64, 32, wgpu::TextureFormat::Rgba8UnormSrgb);
+        let index = framebuffer.add_layer(BlendMode::Additive);
+        framebuffer.layer_mut(index).set_opacity(0.5);
+        framebuffer.layer_mut(index).set_visible(false);
+        let effect = framebuffer
+            .post_process_mut()
+            .push(PostEffect::grayscale(1.0))
+            .unwrap();
+        framebuffer.post_process_mut().set_enabled(effect, false);
+
+        let device = instance.headless_device().unwrap();
+        let recreated = framebuffer.recreate(&device).unwrap();
+
+        assert_eq!(recreated.layers().len(), 1);
+        let layer = recreated.layer(0);
+        assert_eq!(layer.blend_mode(), BlendMode::Additive);
+        assert_eq!(layer.opacity(), 0.5);
+        assert!(!layer.is_visible());
+        assert_eq!((layer.target().width(), layer.target().height()), (64, 32));
+        assert_eq!(layer.target().format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(recreated.post_process().len(), 1);
+        assert!(!recreated.post_process().is_enabled(0));
+        assert!(!recreated.post_process().needs_texture(0));
+        assert_eq!(recreated.frame_index(), framebuffer.frame_index());
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::atomic::AtomicU64;
+
+use crate::RenderInstance;
+
+pub type AdapterFilter = Box<dyn Fn(&wgpu::AdapterInfo) -> bool + Send + Sync>;
+
+#[derive(Default)]
+pub struct DeviceOptions {
+    pub power_preference: wgpu::PowerPreference,
+    pub force_fallback_adapter: bool,
+    pub required_features: wgpu::Features,
+    pub required_limits: wgpu::Limits,
+    pub adapter_filter: Option<AdapterFilter>,
+}
+
+pub struct RenderInstanceBuilder {
+    backends: wgpu::Backends,
+    options: DeviceOptions,
+}
+
+impl RenderInstanceBuilder {
+    pub fn new() -> RenderInstanceBuilder {
+        RenderInstanceBuilder {
+            backends: wgpu::Backends::PRIMARY,
+            options: DeviceOptions::default(),
+        }
+    }
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.options.power_preference = power_preference;
+        self
+    }
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.options.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+    pub fn required_features(mut self, required_features: wgpu::Features) -> Self {
+        self.options.required_features = required_features;
+        self
+    }
+    pub fn required_limits(mut self, required_limits: wgpu::Limits) -> Self {
+        self.options.required_limits = required_limits;
+        self
+    }
+    pub fn adapter_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&wgpu::AdapterInfo) -> bool + Send + Sync + 'static,
+    {
+        self.options.adapter_filter = Some(Box::new(filter));
+        self
+    }
+    pub fn device_options(mut self, options: DeviceOptions) -> Self {
+        self.options = options;
+        self
+    }
+    pub fn build(self) -> RenderInstance {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends,
+            ..Default::default()
+        });
+
+        RenderInstance {
+            instance,
+            backends: self.backends,
+            options: self.options,
+            generation: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Default for RenderInstanceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
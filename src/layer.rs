@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::target::RenderTarget;
+use crate::texture::RenderableTexture;
+use crate::{ArcedRenderDevice, Result};
+
+const LAYER_SHADER: &str = include_str!("shaders/layer.wgsl");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    #[default]
+    Alpha,
+    Additive,
+    Multiply,
+    Screen,
+    Premultiplied,
+}
+
+pub struct Layer {
+    device: ArcedRenderDevice,
+    target: RenderTarget,
+    opacity: f32,
+    blend_mode: BlendMode,
+    visible: bool,
+    params_buffer: wgpu::Buffer,
+    bind_group: Mutex<Option<wgpu::BindGroup>>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LayerParams {
+    opacity: f32,
+    mode: u32,
+    padding: [u32; 2],
+}
+
+pub(crate) struct LayerCompositor {
+    device: ArcedRenderDevice,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipelines: HashMap<BlendMode, wgpu::RenderPipeline>,
+    target: RenderableTexture,
+}
+
+impl BlendMode {
+    const ALL: [BlendMode; 5] = [
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Premultiplied,
+    ];
+
+    fn blend_state(self) -> wgpu::BlendState {
+        let keep_alpha = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let color = |src_factor, dst_factor| wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: keep_alpha,
+        };
+        match self {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => color(wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+            BlendMode::Multiply => color(wgpu::BlendFactor::Dst, wgpu::BlendFactor::Zero),
+            BlendMode::Screen => color(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrc),
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        }
+    }
+}
+
+impl Layer {
+    pub(crate) fn new(
+        device: &ArcedRenderDevice,
+        target: RenderTarget,
+        blend_mode: BlendMode,
+    ) -> Layer {
+        let params_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Layer Params Buffer"),
+            contents: bytemuck::bytes_of(&LayerParams::new(1.0, blend_mode)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Layer {
+            device: device.clone(),
+            target,
+            opacity: 1.0,
+            blend_mode,
+            visible: true,
+            params_buffer,
+            bind_group: Mutex::new(None),
+        }
+    }
+    pub fn recreate(&self, device: &ArcedRenderDevice) -> Result<Layer> {
+        let mut layer = Layer::new(device, self.target.recreate(device)?, self.blend_mode);
+        layer.set_opacity(self.opacity);
+        layer.set_visible(self.visible);
+        Ok(layer)
+    }
+    pub const fn target(&self) -> &RenderTarget {
+        &self.target
+    }
+    pub fn target_mut(&mut self) -> &mut RenderTarget {
+        *self.bind_group.get_mut().unwrap() = None;
+        &mut self.target
+    }
+    pub fn set_opacity(&mut self, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            self.write_params();
+        }
+    }
+    pub const fn opacity(&self) -> f32 {
+        self.opacity
+    }
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        if self.blend_mode != blend_mode {
+            self.blend_mode = blend_mode;
+            self.write_params();
+        }
+    }
+    pub const fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+    pub const fn is_visible(&self) -> bool {
+        self.visible
+    }
+    fn write_params(&self) {
+        self.device.queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::bytes_of(&LayerParams::new(self.opacity, self.blend_mode)),
+        );
+    }
+}
+
+impl LayerParams {
+    const fn new(opacity: f32, blend_mode: BlendMode) -> LayerParams {
+        LayerParams {
+            opacity,
+            mode: blend_mode as u32,
+            padding: [0; 2],
+        }
+    }
+}
+
+impl LayerCompositor {
+    pub(crate) fn new(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> LayerCompositor {
+        let bind_group_layout =
+            device
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Layer Bind Group Layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                multisampled: false,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+        let pipeline_layout =
+            device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Layer Pipeline Layout"),
+                    bind_group_layouts: &[&bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+        let shader = device
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Layer Shader"),
+                source: wgpu::ShaderSource::Wgsl(LAYER_SHADER.into()),
+            });
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|&blend_mode| {
+                let pipeline =
+                    device
+                        .device
+                        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                            label: Some("Layer Render Pipeline"),
+                            layout: Some(&pipeline_layout),
+                            vertex: wgpu::VertexState {
+                                module: &shader,
+                                entry_point: "vs_main",
+                                buffers: &[],
+                                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                            },
+                            fragment: Some(wgpu::FragmentState {
+                                module: &shader,
+                                entry_point: "fs_main",
+                                targets: &[Some(wgpu::ColorTargetState {
+                                    format,
+                                    blend: Some(blend_mode.blend_state()),
+                                    write_mask: wgpu::ColorWrites::ALL,
+                                })],
+                                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                            }),
+                            primitive: wgpu::PrimitiveState::default(),
+                            depth_stencil: None,
+                            multisample: wgpu::MultisampleState::default(),
+                            multiview: None,
+                            cache: None,
+                        });
+                (blend_mode, pipeline)
+            })
+            .collect();
+
+        LayerCompositor {
+            device: device.clone(),
+            bind_group_layout,
+            pipelines,
+            target: create_target(device, width, height, format),
+        }
+    }
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.target = create_target(&self.device, width, height, self.target.format());
+    }
+    pub(crate) fn run<'a>(
+        &'a self,
+        encoder: &mut wgpu::CommandEncoder,
+        base: &RenderableTexture,
+        layers: &[Layer],
+    ) -> Option<&'a RenderableTexture> {
+        if !layers.iter().any(|layer| layer.visible) {
+            return None;
+        }
+
+        encoder.copy_texture_to_texture(
+            base.wgpu_texture().as_image_copy(),
+            self.target.wgpu_texture().as_image_copy(),
+            self.target.wgpu_texture().size(),
+        );
+        let view = self.target.create_view();
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Layer Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            for layer in layers.iter().filter(|layer| layer.visible) {
+                let mut bind_group = layer.bind_group.lock().unwrap();
+                let bind_group = bind_group.get_or_insert_with(|| self.create_bind_group(layer));
+                render_pass.set_pipeline(&self.pipelines[&layer.blend_mode]);
+                render_pass.set_bind_group(0, bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+
+        Some(&self.target)
+    }
+    fn create_bind_group(&self, layer: &Layer) -> wgpu::BindGroup {
+        self.device
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Layer Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&layer.target.create_view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: layer.params_buffer.as_entire_binding(),
+                    },
+                ],
+            })
+    }
+}
+
+fn create_target(
+    device: &ArcedRenderDevice,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> RenderableTexture {
+    RenderableTexture::uninitialized(
+        device,
+        width,
+        height,
+        format,
+        1,
+        wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+    )
+}
@@ -1,20 +1,70 @@
+pub mod app;
+pub mod atlas;
+pub mod camera;
+pub mod decode;
+pub mod depth;
+pub mod error;
+pub mod frame;
+pub mod framebuffer;
+pub mod instance;
+pub mod layer;
+pub mod math;
+pub mod mesh;
+pub mod mipmap;
+pub mod pixels;
+pub mod postprocess;
 pub mod reexports;
+pub mod scaling;
+pub mod shader;
+pub mod shapes;
+pub mod sprite;
+pub mod surface;
+pub mod target;
+pub mod text;
 pub mod texture;
 
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use pollster::FutureExt;
-use texture::{RenderableTexture, TextureSampler};
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use winit::window::Window;
 
+pub use app::{App, AppContext, WindowConfig};
+pub use atlas::{TextureAtlas, TextureAtlasBuilder};
+pub use camera::{Camera2D, Camera3D, Projection};
+pub use decode::Image;
+pub use depth::StencilMode;
+pub use error::{Error, Result};
+pub use frame::{Frame, Renderer};
+pub use framebuffer::FrameBuffer;
+pub use instance::{DeviceOptions, RenderInstanceBuilder};
+pub use layer::{BlendMode, Layer};
+pub use math::{Mat4, Rect, Transform2D, TransformStack};
+pub use mesh::{DirectionalLight, Material, Mesh, MeshRenderer, MeshVertex};
+pub use mipmap::MipmapGenerator;
+pub use postprocess::{PostEffect, PostProcessChain};
+pub use scaling::{ScalingMode, Viewport};
+pub use shader::{ShaderDiagnostic, ShaderSource};
+pub use shapes::{ShapeRenderer, ShapeVertex};
+pub use sprite::{Sprite, SpriteBatch};
+pub use surface::{SurfaceOptions, Vsync};
+pub use target::RenderTarget;
+pub use text::{BitmapFont, Font, FontId, Text, TextAlign, TextRenderer};
+
 pub struct RenderInstance {
     pub instance: wgpu::Instance,
+    backends: wgpu::Backends,
+    options: DeviceOptions,
+    generation: AtomicU64,
 }
 
 pub struct RenderDevice {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    adapter: Arc<wgpu::Adapter>,
+    generation: u64,
+    lost: Arc<AtomicBool>,
+    mipmap_generator: Mutex<Option<MipmapGenerator>>,
 }
 
 pub type ArcedRenderDevice = Arc<RenderDevice>;
@@ -23,378 +73,284 @@ pub struct RenderSurface<'a> {
     pub surface: wgpu::Surface<'a>,
     pub configuration: wgpu::SurfaceConfiguration,
     pub format: wgpu::TextureFormat,
-    pub adapter: wgpu::Adapter,
-}
-
-pub struct FrameBuffer {
-    device: ArcedRenderDevice,
-    texture_bind_group_layout: wgpu::BindGroupLayout,
-    texture: RenderableTexture,
-    sampler: TextureSampler,
-    pipeline: wgpu::RenderPipeline,
-    texture_bind_group: wgpu::BindGroup,
-    global_buffer: wgpu::Buffer,
-    global_bind_group: wgpu::BindGroup,
+    pub adapter: Arc<wgpu::Adapter>,
+    pub capabilities: wgpu::SurfaceCapabilities,
 }
 
 impl RenderInstance {
     pub fn new() -> RenderInstance {
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
-            ..Default::default()
-        });
+        RenderInstanceBuilder::new().build()
+    }
+    pub fn builder() -> RenderInstanceBuilder {
+        RenderInstanceBuilder::new()
+    }
+    pub fn adapters(&self) -> Vec<wgpu::AdapterInfo> {
+        self.instance
+            .enumerate_adapters(self.backends)
+            .iter()
+            .map(|adapter| adapter.get_info())
+            .collect()
+    }
+    pub const fn device_options(&self) -> &DeviceOptions {
+        &self.options
+    }
 
-        RenderInstance { instance }
+    pub fn surface_from_window<'a>(&self, window: &Arc<Window>) -> Result<RenderSurface<'a>> {
+        self.surface_from_window_with_options(window, &SurfaceOptions::default())
     }
+    pub fn surface_from_window_with_options<'a>(
+        &self,
+        window: &Arc<Window>,
+        options: &SurfaceOptions,
+    ) -> Result<RenderSurface<'a>> {
+        let surface = self.instance.create_surface(window.clone())?;
 
-    pub fn surface_from_window<'a>(&self, window: &Arc<Window>) -> RenderSurface<'a> {
-        let size = window.inner_size();
+        let adapter =
+            Arc::new(self.request_adapter(Some(&surface), self.options.force_fallback_adapter)?);
+
+        RenderSurface::new(window, surface, adapter, options)
+    }
+    pub fn surface_for_device<'a>(
+        &self,
+        window: &Arc<Window>,
+        device: &ArcedRenderDevice,
+        options: &SurfaceOptions,
+    ) -> Result<RenderSurface<'a>> {
+        let surface = self.instance.create_surface(window.clone())?;
+        if !device.adapter.is_surface_supported(&surface) {
+            return Err(Error::UnsupportedSurface);
+        }
+
+        let surface = RenderSurface::new(window, surface, device.adapter.clone(), options)?;
+        surface.reconfigure(device);
+
+        Ok(surface)
+    }
 
-        let surface = self.instance.create_surface(window.clone()).unwrap();
+    pub fn device_from_instance(&self) -> Result<ArcedRenderDevice> {
+        let adapter = self.request_adapter(None, self.options.force_fallback_adapter)?;
 
+        self.create_device(Arc::new(adapter))
+    }
+    pub fn headless_device(&self) -> Result<ArcedRenderDevice> {
         let adapter = self
-            .instance
-            .request_adapter(&wgpu::RequestAdapterOptionsBase {
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-                ..Default::default()
-            })
-            .block_on()
-            .unwrap();
+            .request_adapter(None, true)
+            .or_else(|_| self.request_adapter(None, false))?;
 
-        let capabilities = surface.get_capabilities(&adapter);
-        let format = capabilities
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(capabilities.formats[0]);
-        let configuration = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width: size.width,
-            height: size.height,
-            present_mode: capabilities.present_modes[0],
-            alpha_mode: capabilities.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
+        self.create_device(Arc::new(adapter))
+    }
+    pub fn device_from_surface<'a>(
+        &self,
+        supported_surface: &RenderSurface<'a>,
+    ) -> Result<ArcedRenderDevice> {
+        let device = self.create_device(supported_surface.adapter.clone())?;
 
-        RenderSurface {
-            surface,
-            configuration,
-            format,
+        supported_surface.reconfigure(&device);
+
+        Ok(device)
+    }
+    fn create_device(&self, adapter: Arc<wgpu::Adapter>) -> Result<ArcedRenderDevice> {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+        Ok(Arc::new(RenderDevice::from_adapter(
             adapter,
-        }
+            &self.options,
+            generation,
+        )?))
     }
+    fn request_adapter(
+        &self,
+        compatible_surface: Option<&wgpu::Surface>,
+        force_fallback_adapter: bool,
+    ) -> Result<wgpu::Adapter> {
+        if let Some(filter) = &self.options.adapter_filter {
+            return self
+                .instance
+                .enumerate_adapters(self.backends)
+                .into_iter()
+                .find(|adapter| {
+                    let info = adapter.get_info();
+                    (!force_fallback_adapter || info.device_type == wgpu::DeviceType::Cpu)
+                        && compatible_surface
+                            .map_or(true, |surface| adapter.is_surface_supported(surface))
+                        && filter(&info)
+                })
+                .ok_or(Error::NoAdapter);
+        }
 
-    pub fn device_from_instance(&self) -> ArcedRenderDevice {
-        let adapter = self
-            .instance
-            .request_adapter(&wgpu::RequestAdapterOptionsBase {
-                force_fallback_adapter: false,
-                compatible_surface: None,
-                ..Default::default()
+        self.instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: self.options.power_preference,
+                force_fallback_adapter,
+                compatible_surface,
             })
             .block_on()
-            .unwrap();
+            .ok_or(Error::NoAdapter)
+    }
+}
 
+impl Default for RenderInstance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderDevice {
+    fn from_adapter(
+        adapter: Arc<wgpu::Adapter>,
+        options: &DeviceOptions,
+        generation: u64,
+    ) -> Result<RenderDevice> {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Render Device"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
+                    required_features: options.required_features,
+                    required_limits: options.required_limits.clone(),
                     ..Default::default()
                 },
                 None,
             )
-            .block_on()
-            .unwrap();
+            .block_on()?;
 
-        Arc::new(RenderDevice { device, queue })
+        let lost = Arc::new(AtomicBool::new(false));
+        let lost_flag = lost.clone();
+        device.set_device_lost_callback(move |reason, _| {
+            if !matches!(
+                reason,
+                wgpu::DeviceLostReason::Dropped | wgpu::DeviceLostReason::ReplacedCallback
+            ) {
+                lost_flag.store(true, Ordering::Release);
+            }
+        });
+
+        Ok(RenderDevice {
+            device,
+            queue,
+            adapter,
+            generation,
+            lost,
+            mipmap_generator: Mutex::new(None),
+        })
     }
-    pub fn device_from_surface<'a>(
+    pub fn adapter(&self) -> &Arc<wgpu::Adapter> {
+        &self.adapter
+    }
+    pub fn format_features(&self, format: wgpu::TextureFormat) -> wgpu::TextureFormatFeatures {
+        let features = self.device.features();
+        if features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+            self.adapter.get_texture_format_features(format)
+        } else {
+            format.guaranteed_format_features(features)
+        }
+    }
+    pub fn supports_sample_count(&self, format: wgpu::TextureFormat, sample_count: u32) -> bool {
+        let features = self.format_features(format);
+        sample_count == 1
+            || features
+                .allowed_usages
+                .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                && features.flags.sample_count_supported(sample_count)
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+    pub fn generate_mipmaps(&self, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        let mut generator = self.mipmap_generator.lock().unwrap();
+        generator
+            .get_or_insert_with(|| MipmapGenerator::new(self))
+            .generate(self, encoder, texture);
+    }
+    pub(crate) fn validation_scope<T>(
         &self,
-        supported_surface: &RenderSurface<'a>,
-    ) -> ArcedRenderDevice {
-        let (device, queue) = supported_surface
-            .adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("Render Device"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    ..Default::default()
-                },
-                None,
-            )
-            .block_on()
-            .unwrap();
-
-        supported_surface
-            .surface
-            .configure(&device, &supported_surface.configuration);
-
-        Arc::new(RenderDevice { device, queue })
+        create: impl FnOnce() -> T,
+    ) -> (T, Option<wgpu::Error>) {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let value = create();
+        (value, self.device.pop_error_scope().block_on())
     }
 }
 
 impl<'a> RenderSurface<'a> {
+    fn new(
+        window: &Window,
+        surface: wgpu::Surface<'a>,
+        adapter: Arc<wgpu::Adapter>,
+        options: &SurfaceOptions,
+    ) -> Result<RenderSurface<'a>> {
+        let size = window.inner_size();
+        let capabilities = surface.get_capabilities(&adapter);
+        let format = options
+            .select_format(&capabilities)
+            .ok_or(Error::UnsupportedSurface)?;
+        let configuration = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode: options.vsync.select(&capabilities),
+            alpha_mode: options.select_alpha_mode(&capabilities),
+            view_formats: vec![],
+            desired_maximum_frame_latency: options.frame_latency,
+        };
+
+        Ok(RenderSurface {
+            surface,
+            configuration,
+            format,
+            adapter,
+            capabilities,
+        })
+    }
     pub fn resize(&mut self, device: &ArcedRenderDevice, width: u32, height: u32) {
         if width == 0 || height == 0 {
             return;
         }
         self.configuration.width = width;
         self.configuration.height = height;
+        self.reconfigure(device);
+    }
+    pub fn reconfigure(&self, device: &ArcedRenderDevice) {
         self.surface.configure(&device.device, &self.configuration);
     }
-}
-
-impl FrameBuffer {
-    pub fn new(device: &ArcedRenderDevice, surface: &RenderSurface) -> FrameBuffer {
-        let global_bind_group_layout =
-            device
-                .device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Texture Bind Group Layout"),
-                    entries: &[wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    }],
-                });
-        let texture_bind_group_layout =
-            device
-                .device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Texture Bind Group Layout"),
-                    entries: &[
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Texture {
-                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                                view_dimension: wgpu::TextureViewDimension::D2,
-                                multisampled: false,
-                            },
-                            count: None,
-                        },
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                            count: None,
-                        },
-                    ],
-                });
-        let sampler = TextureSampler::new(device);
-        let texture = RenderableTexture::from_surface(device, surface);
-        let texture_view = texture
-            .wgpu_texture()
-            .create_view(&wgpu::TextureViewDescriptor {
-                label: Some("Texture View"),
-                ..Default::default()
-            });
-        let global_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("Global Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[
-                surface.configuration.width as f32,
-                surface.configuration.height as f32,
-            ]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-        let global_bind_group = device.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Global Uniform Bind Group"),
-            layout: &global_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(global_buffer.as_entire_buffer_binding()),
-            }],
-        });
-        let texture_bind_group = device.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Texture Bind Group"),
-            layout: &texture_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler.wgpu_sampler()),
-                },
-            ],
-        });
-        let pipeline_layout =
-            device
-                .device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("FrameBuffer Present Pipeline"),
-                    bind_group_layouts: &[&texture_bind_group_layout, &global_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-        let shader = device
-            .device
-            .create_shader_module(wgpu::include_wgsl!("shaders/present.wgsl"));
-        let pipeline = device
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface.configuration.format,
-                        blend: Some(wgpu::BlendState::REPLACE),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            });
-
-        FrameBuffer {
-            device: device.clone(),
-            texture_bind_group_layout,
-            texture,
-            sampler,
-            pipeline,
-            texture_bind_group,
-            global_buffer,
-            global_bind_group,
+    pub fn set_present_mode(
+        &mut self,
+        device: &ArcedRenderDevice,
+        present_mode: wgpu::PresentMode,
+    ) -> Result<()> {
+        if !self.capabilities.present_modes.contains(&present_mode) {
+            return Err(Error::UnsupportedPresentMode(present_mode));
         }
+        self.configuration.present_mode = present_mode;
+        self.reconfigure(device);
+        Ok(())
     }
-    pub fn present_with_encoder(&self, surface: &RenderSurface, mut encoder: wgpu::CommandEncoder) {
-        let output = surface.surface.get_current_texture().unwrap();
-        let surface_view = output.texture.create_view(&wgpu::TextureViewDescriptor {
-            label: Some("Surface Texture View"),
-            ..Default::default()
-        });
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Present Framebuffer Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.texture_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.global_bind_group, &[]);
-            render_pass.draw(0..3, 0..1);
-        }
-
-        self.device.queue.submit([encoder.finish()]);
-        output.present();
+    pub fn set_vsync(&mut self, device: &ArcedRenderDevice, vsync: Vsync) {
+        self.configuration.present_mode = vsync.select(&self.capabilities);
+        self.reconfigure(device);
     }
-    pub fn present(&self, surface: &RenderSurface) {
-        let output = surface.surface.get_current_texture().unwrap();
-        let surface_view = output.texture.create_view(&wgpu::TextureViewDescriptor {
-            label: Some("Surface Texture View"),
-            ..Default::default()
-        });
-
-        let mut encoder =
-            self.device
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Present Framebuffer Command Encoder"),
-                });
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Present Framebuffer Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.texture_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.global_bind_group, &[]);
-            render_pass.draw(0..3, 0..1);
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.configuration.present_mode
+    }
+    pub(crate) fn acquire(&self, device: &ArcedRenderDevice) -> Result<wgpu::SurfaceTexture> {
+        match self.surface.get_current_texture() {
+            Ok(output) => Ok(output),
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                self.reconfigure(device);
+                Ok(self.surface.get_current_texture()?)
+            }
+            Err(error) => Err(error.into()),
         }
-
-        self.device.queue.submit([encoder.finish()]);
-        output.present();
     }
-    pub fn rebuild(&mut self, surface: &RenderSurface) {
-        self.texture = RenderableTexture::from_surface(&self.device, surface);
-        let texture_view = self
-            .texture
-            .wgpu_texture()
-            .create_view(&wgpu::TextureViewDescriptor {
-                label: Some("Texture View"),
-                ..Default::default()
-            });
-        self.texture_bind_group =
-            self.device
-                .device
-                .create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("Texture Bind Group"),
-                    layout: &self.texture_bind_group_layout,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(&texture_view),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&self.sampler.wgpu_sampler()),
-                        },
-                    ],
-                });
+}
 
-        self.device.queue.write_buffer(&self.global_buffer, 0, bytemuck::cast_slice(&[
-            surface.configuration.width as f32,
-            surface.configuration.height as f32
-        ]));
-    }
-    pub const fn renderable_texture(&self) -> &RenderableTexture {
-        &self.texture
-    }
+pub(crate) fn color_to_array(color: wgpu::Color) -> [f32; 4] {
+    [
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ]
 }
@@ -0,0 +1,555 @@
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub x_axis: [f32; 2],
+    pub y_axis: [f32; 2],
+    pub translation: [f32; 2],
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransformStack {
+    current: Transform2D,
+    stack: Vec<Transform2D>,
+}
+
+impl Transform2D {
+    pub const IDENTITY: Transform2D = Transform2D {
+        x_axis: [1.0, 0.0],
+        y_axis: [0.0, 1.0],
+        translation: [0.0, 0.0],
+    };
+
+    pub const fn translation(x: f32, y: f32) -> Transform2D {
+        Transform2D {
+            translation: [x, y],
+            ..Transform2D::IDENTITY
+        }
+    }
+    pub fn rotation(angle: f32) -> Transform2D {
+        let (sin, cos) = angle.sin_cos();
+        Transform2D {
+            x_axis: [cos, sin],
+            y_axis: [-sin, cos],
+            translation: [0.0, 0.0],
+        }
+    }
+    pub const fn scale(x: f32, y: f32) -> Transform2D {
+        Transform2D {
+            x_axis: [x, 0.0],
+            y_axis: [0.0, y],
+            translation: [0.0, 0.0],
+        }
+    }
+    pub fn transform_point(&self, point: [f32; 2]) -> [f32; 2] {
+        [
+            self.x_axis[0] * point[0] + self.y_axis[0] * point[1] + self.translation[0],
+            self.x_axis[1] * point[0] + self.y_axis[1] * point[1] + self.translation[1],
+        ]
+    }
+    pub fn inverse(&self) -> Transform2D {
+        let determinant = self.x_axis[0] * self.y_axis[1] - self.y_axis[0] * self.x_axis[1];
+        let inverse_determinant = if determinant == 0.0 {
+            0.0
+        } else {
+            1.0 / determinant
+        };
+        let x_axis = [
+            self.y_axis[1] * inverse_determinant,
+            -self.x_axis[1] * inverse_determinant,
+        ];
+        let y_axis = [
+            -self.y_axis[0] * inverse_determinant,
+            self.x_axis[0] * inverse_determinant,
+        ];
+        let translation = [
+            -(x_axis[0] * self.translation[0] + y_axis[0] * self.translation[1]),
+            -(x_axis[1] * self.translation[0] + y_axis[1] * self.translation[1]),
+        ];
+        Transform2D {
+            x_axis,
+            y_axis,
+            translation,
+        }
+    }
+    pub fn to_cols_array_2d(&self) -> [[f32; 4]; 4] {
+        [
+            [self.x_axis[0], self.x_axis[1], 0.0, 0.0],
+            [self.y_axis[0], self.y_axis[1], 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [self.translation[0], self.translation[1], 0.0, 1.0],
+        ]
+    }
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Transform2D::IDENTITY
+    }
+}
+
+impl std::ops::Mul for Transform2D {
+    type Output = Transform2D;
+
+    fn mul(self, rhs: Transform2D) -> Transform2D {
+        Transform2D {
+            x_axis: [
+                self.x_axis[0] * rhs.x_axis[0] + self.y_axis[0] * rhs.x_axis[1],
+                self.x_axis[1] * rhs.x_axis[0] + self.y_axis[1] * rhs.x_axis[1],
+            ],
+            y_axis: [
+                self.x_axis[0] * rhs.y_axis[0] + self.y_axis[0] * rhs.y_axis[1],
+                self.x_axis[1] * rhs.y_axis[0] + self.y_axis[1] * rhs.y_axis[1],
+            ],
+            translation: self.transform_point(rhs.translation),
+        }
+    }
+}
+
+impl TransformStack {
+    pub fn new() -> TransformStack {
+        TransformStack::default()
+    }
+    pub fn push(&mut self) {
+        self.stack.push(self.current);
+    }
+    pub fn pop(&mut self) {
+        self.current = self.stack.pop().unwrap_or_default();
+    }
+    pub fn reset(&mut self) {
+        self.current = Transform2D::IDENTITY;
+        self.stack.clear();
+    }
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.current = self.current * Transform2D::translation(x, y);
+    }
+    pub fn rotate(&mut self, angle: f32) {
+        self.current = self.current * Transform2D::rotation(angle);
+    }
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.current = self.current * Transform2D::scale(x, y);
+    }
+    pub fn apply(&mut self, transform: Transform2D) {
+        self.current = self.current * transform;
+    }
+    pub const fn current(&self) -> Transform2D {
+        self.current
+    }
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+    pub fn transform_point(&self, point: [f32; 2]) -> [f32; 2] {
+        self.current.transform_point(point)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub cols: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        cols: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub const fn translation(x: f32, y: f32, z: f32) -> Mat4 {
+        Mat4 {
+            cols: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [x, y, z, 1.0],
+            ],
+        }
+    }
+    pub const fn scale(x: f32, y: f32, z: f32) -> Mat4 {
+        Mat4 {
+            cols: [
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+    pub fn rotation_x(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4 {
+            cols: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, cos, sin, 0.0],
+                [0.0, -sin, cos, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+    pub fn rotation_y(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4 {
+            cols: [
+                [cos, 0.0, -sin, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [sin, 0.0, cos, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+    pub fn rotation_z(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4 {
+            cols: [
+                [cos, sin, 0.0, 0.0],
+                [-sin, cos, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let focal = 1.0 / (fov_y / 2.0).tan();
+        let range = far / (near - far);
+        Mat4 {
+            cols: [
+                [focal / aspect, 0.0, 0.0, 0.0],
+                [0.0, focal, 0.0, 0.0],
+                [0.0, 0.0, range, -1.0],
+                [0.0, 0.0, range * near, 0.0],
+            ],
+        }
+    }
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        let width = 1.0 / (right - left);
+        let height = 1.0 / (top - bottom);
+        let range = 1.0 / (near - far);
+        Mat4 {
+            cols: [
+                [2.0 * width, 0.0, 0.0, 0.0],
+                [0.0, 2.0 * height, 0.0, 0.0],
+                [0.0, 0.0, range, 0.0],
+                [
+                    -(left + right) * width,
+                    -(top + bottom) * height,
+                    range * near,
+                    1.0,
+                ],
+            ],
+        }
+    }
+    pub fn look_at(eye: [f32; 3], target: [f32; 3], up: [f32; 3]) -> Mat4 {
+        let forward = normalize(sub(target, eye));
+        let side = normalize(cross(forward, up));
+        let up = cross(side, forward);
+        Mat4 {
+            cols: [
+                [side[0], up[0], -forward[0], 0.0],
+                [side[1], up[1], -forward[1], 0.0],
+                [side[2], up[2], -forward[2], 0.0],
+                [-dot(side, eye), -dot(up, eye), dot(forward, eye), 1.0],
+            ],
+        }
+    }
+    pub fn normal_matrix(&self) -> [[f32; 3]; 3] {
+        let [x_axis, y_axis, z_axis] =
+            [0, 1, 2].map(|i| [self.cols[i][0], self.cols[i][1], self.cols[i][2]]);
+        let cofactors = [
+            cross(y_axis, z_axis),
+            cross(z_axis, x_axis),
+            cross(x_axis, y_axis),
+        ];
+        let determinant = dot(x_axis, cofactors[0]);
+        let inverse_determinant = if determinant == 0.0 {
+            1.0
+        } else {
+            1.0 / determinant
+        };
+        cofactors.map(|column| column.map(|value| value * inverse_determinant))
+    }
+    pub fn transform_point(&self, point: [f32; 3]) -> [f32; 3] {
+        let [x, y, z, w] = self.transform([point[0], point[1], point[2], 1.0]);
+        [x / w, y / w, z / w]
+    }
+    pub fn transform(&self, vector: [f32; 4]) -> [f32; 4] {
+        let mut result = [0.0; 4];
+        for (column, &value) in self.cols.iter().zip(&vector) {
+            for (row, result) in result.iter_mut().enumerate() {
+                *result += column[row] * value;
+            }
+        }
+        result
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+impl std::ops::Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        Mat4 {
+            cols: rhs.cols.map(|column| self.transform(column)),
+        }
+    }
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    if length == 0.0 {
+        a
+    } else {
+        [a[0] / length, a[1] / length, a[2] / length]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close<const N: usize>(actual: [f32; N], expected: [f32; N]) {
+        for (actual_value, expected_value) in actual.iter().zip(&expected) {
+            assert!(
+                (actual_value - expected_value).abs() < 1e-4,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn transform_basic_points() {
+        assert_eq!(
+            Transform2D::translation(3.0, -2.0).transform_point([1.0, 1.0]),
+            [4.0, -1.0]
+        );
+        assert_eq!(
+            Transform2D::scale(2.0, 3.0).transform_point([1.0, 1.0]),
+            [2.0, 3.0]
+        );
+        assert_close(
+            Transform2D::rotation(std::f32::consts::FRAC_PI_2).transform_point([1.0, 0.0]),
+            [0.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn transform_composition_applies_right_first() {
+        let transform = Transform2D::translation(10.0, 0.0) * Transform2D::scale(2.0, 2.0);
+        assert_eq!(transform.transform_point([1.0, 1.0]), [12.0, 2.0]);
+        let transform = Transform2D::scale(2.0, 2.0) * Transform2D::translation(10.0, 0.0);
+        assert_eq!(transform.transform_point([1.0, 1.0]), [22.0, 2.0]);
+    }
+
+    #[test]
+    fn transform_inverse_round_trips() {
+        let transform = Transform2D::translation(5.0, -7.0)
+            * Transform2D::rotation(0.7)
+            * Transform2D::scale(2.0, 0.5);
+        let point = [3.0, 4.0];
+        assert_close(
+            transform
+                .inverse()
+                .transform_point(transform.transform_point(point)),
+            point,
+        );
+        let identity = transform * transform.inverse();
+        assert_close(identity.x_axis, [1.0, 0.0]);
+        assert_close(identity.y_axis, [0.0, 1.0]);
+        assert_close(identity.translation, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn singular_transform_inverse_is_finite() {
+        let inverse = Transform2D::scale(0.0, 1.0).inverse();
+        assert!(inverse
+            .x_axis
+            .iter()
+            .chain(&inverse.y_axis)
+            .chain(&inverse.translation)
+            .all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn transform_to_cols_array() {
+        let transform = Transform2D::translation(3.0, 4.0) * Transform2D::scale(2.0, 5.0);
+        assert_eq!(
+            transform.to_cols_array_2d(),
+            [
+                [2.0, 0.0, 0.0, 0.0],
+                [0.0, 5.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [3.0, 4.0, 0.0, 1.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn transform_stack_push_and_pop() {
+        let mut stack = TransformStack::new();
+        stack.translate(10.0, 0.0);
+        stack.push();
+        stack.scale(2.0, 2.0);
+        assert_eq!(stack.depth(), 1);
+        assert_eq!(stack.transform_point([1.0, 1.0]), [12.0, 2.0]);
+        stack.pop();
+        assert_eq!(stack.depth(), 0);
+        assert_eq!(stack.transform_point([1.0, 1.0]), [11.0, 1.0]);
+        stack.pop();
+        assert_eq!(stack.current(), Transform2D::IDENTITY);
+    }
+
+    #[test]
+    fn transform_stack_reset() {
+        let mut stack = TransformStack::new();
+        stack.push();
+        stack.rotate(1.0);
+        stack.apply(Transform2D::translation(1.0, 2.0));
+        stack.reset();
+        assert_eq!(stack.depth(), 0);
+        assert_eq!(stack.current(), Transform2D::IDENTITY);
+    }
+
+    #[test]
+    fn mat4_basic_points() {
+        let point = [1.0, 2.0, 3.0];
+        assert_eq!(Mat4::IDENTITY.transform_point(point), point);
+        assert_eq!(
+            Mat4::translation(1.0, -1.0, 2.0).transform_point(point),
+            [2.0, 1.0, 5.0]
+        );
+        assert_eq!(
+            Mat4::scale(2.0, 3.0, 4.0).transform_point(point),
+            [2.0, 6.0, 12.0]
+        );
+        let quarter = std::f32::consts::FRAC_PI_2;
+        assert_close(
+            Mat4::rotation_x(quarter).transform_point([0.0, 1.0, 0.0]),
+            [0.0, 0.0, 1.0],
+        );
+        assert_close(
+            Mat4::rotation_y(quarter).transform_point([0.0, 0.0, 1.0]),
+            [1.0, 0.0, 0.0],
+        );
+        assert_close(
+            Mat4::rotation_z(quarter).transform_point([1.0, 0.0, 0.0]),
+            [0.0, 1.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn mat4_multiplication_applies_right_first() {
+        let transform = Mat4::translation(10.0, 0.0, 0.0) * Mat4::scale(2.0, 2.0, 2.0);
+        assert_eq!(transform.transform_point([1.0, 1.0, 1.0]), [12.0, 2.0, 2.0]);
+        assert_eq!(Mat4::IDENTITY * transform, transform);
+        assert_eq!(transform * Mat4::IDENTITY, transform);
+    }
+
+    #[test]
+    fn look_at_moves_eye_to_origin() {
+        let view = Mat4::look_at([3.0, 4.0, 5.0], [3.0, 4.0, 0.0], [0.0, 1.0, 0.0]);
+        assert_close(view.transform_point([3.0, 4.0, 5.0]), [0.0, 0.0, 0.0]);
+        assert_close(view.transform_point([3.0, 4.0, 0.0]), [0.0, 0.0, -5.0]);
+        assert_close(view.transform_point([4.0, 5.0, 5.0]), [1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn projections_map_near_and_far_to_depth_range() {
+        let perspective = Mat4::perspective(std::f32::consts::FRAC_PI_2, 2.0, 0.1, 100.0);
+        assert_close(
+            perspective.transform_point([0.0, 0.0, -0.1]),
+            [0.0, 0.0, 0.0],
+        );
+        assert_close(
+            perspective.transform_point([0.0, 0.0, -100.0]),
+            [0.0, 0.0, 1.0],
+        );
+        let [x, y, _] = perspective.transform_point([2.0, 1.0, -1.0]);
+        assert_close([x, y], [1.0, 1.0]);
+
+        let orthographic = Mat4::orthographic(-4.0, 4.0, -3.0, 3.0, 1.0, 11.0);
+        assert_close(
+            orthographic.transform_point([-4.0, -3.0, -1.0]),
+            [-1.0, -1.0, 0.0],
+        );
+        assert_close(
+            orthographic.transform_point([4.0, 3.0, -11.0]),
+            [1.0, 1.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn normal_matrix_is_inverse_transpose() {
+        let rotation = Mat4::rotation_y(0.6);
+        let normal = rotation.normal_matrix();
+        for (column, rotation_column) in normal.iter().zip(&rotation.cols) {
+            assert_close(
+                *column,
+                [rotation_column[0], rotation_column[1], rotation_column[2]],
+            );
+        }
+
+        let transform =
+            Mat4::translation(5.0, 0.0, 0.0) * Mat4::rotation_z(0.3) * Mat4::scale(4.0, 1.0, 0.5);
+        let apply = |matrix: &[[f32; 3]; 3], vector: [f32; 3]| {
+            [0, 1, 2].map(|row| (0..3).map(|i| matrix[i][row] * vector[i]).sum::<f32>())
+        };
+        let [x, y, z, _] = transform.transform([1.0, 1.0, 0.0, 0.0]);
+        let normal = apply(&transform.normal_matrix(), [1.0, -1.0, 0.0]);
+        assert!(dot([x, y, z], normal).abs() < 1e-5);
+        assert_close(
+            apply(&Mat4::scale(2.0, 4.0, 0.5).normal_matrix(), [1.0, 1.0, 1.0]),
+            [0.5, 0.25, 2.0],
+        );
+    }
+}
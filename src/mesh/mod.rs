@@ -0,0 +1,566 @@
+mod obj;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::camera::Camera3D;
+use crate::depth::{depth_stencil_attachment, StencilMode};
+use crate::math::{normalize, Mat4};
+use crate::texture::{
+    create_texture_bind_group, create_texture_bind_group_layout, RenderableTexture, Texture,
+    TextureOptions, TextureSampler,
+};
+use crate::{color_to_array, ArcedRenderDevice, Result};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct MeshInstance {
+    model: [[f32; 4]; 4],
+    normal: [[f32; 3]; 3],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct MeshGlobals {
+    view_projection: [[f32; 4]; 4],
+    light_direction: [f32; 4],
+    light_color: [f32; 4],
+    ambient: [f32; 4],
+}
+
+#[derive(Clone)]
+pub struct Mesh {
+    inner: Arc<MeshInner>,
+}
+
+struct MeshInner {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+#[derive(Clone)]
+pub struct Material {
+    pub base_color: wgpu::Color,
+    pub texture: Option<Texture>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: [f32; 3],
+    pub color: wgpu::Color,
+}
+
+struct MeshDraw {
+    mesh: Mesh,
+    texture: Option<Texture>,
+    instance: MeshInstance,
+}
+
+type PipelineKey = (wgpu::TextureFormat, wgpu::TextureFormat, u32);
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+pub struct MeshRenderer {
+    device: ArcedRenderDevice,
+    shader: wgpu::ShaderModule,
+    globals_bind_group_layout: wgpu::BindGroupLayout,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    sampler: TextureSampler,
+    bind_groups: HashMap<wgpu::Id<wgpu::Texture>, wgpu::BindGroup>,
+    depth: Option<wgpu::Texture>,
+    white: Texture,
+    camera: Camera3D,
+    light: DirectionalLight,
+    ambient: wgpu::Color,
+    draws: Vec<MeshDraw>,
+}
+
+impl MeshVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+        2 => Float32x2,
+        3 => Float32x4
+    ];
+
+    pub fn new(position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> MeshVertex {
+        MeshVertex {
+            position,
+            normal,
+            uv,
+            color: [1.0; 4],
+        }
+    }
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl MeshInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 8] = wgpu::vertex_attr_array![
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Float32x4,
+        8 => Float32x3,
+        9 => Float32x3,
+        10 => Float32x3,
+        11 => Float32x4
+    ];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl Mesh {
+    pub fn new(device: &ArcedRenderDevice, vertices: &[MeshVertex], indices: &[u32]) -> Mesh {
+        let vertex_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Mesh Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Mesh Index Buffer"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Mesh {
+            inner: Arc::new(MeshInner {
+                vertex_buffer,
+                index_buffer,
+                index_count: indices.len() as u32,
+            }),
+        }
+    }
+    pub fn from_obj(device: &ArcedRenderDevice, source: &str) -> Result<Mesh> {
+        let (vertices, indices) = obj::parse(source)?;
+        Ok(Mesh::new(device, &vertices, &indices))
+    }
+    pub fn from_obj_path(device: &ArcedRenderDevice, path: impl AsRef<Path>) -> Result<Mesh> {
+        Mesh::from_obj(device, &std::fs::read_to_string(path)?)
+    }
+    pub fn cube(device: &ArcedRenderDevice, size: f32) -> Mesh {
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        let corners = [
+            (-1.0, -1.0, [0.0, 1.0]),
+            (1.0, -1.0, [1.0, 1.0]),
+            (1.0, 1.0, [1.0, 0.0]),
+            (-1.0, 1.0, [0.0, 0.0]),
+        ];
+        let half = size / 2.0;
+
+        let mut vertices = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+        for (normal, u, v) in faces {
+            let base = vertices.len() as u32;
+            for (su, sv, uv) in corners {
+                let position =
+                    [0, 1, 2].map(|axis| (normal[axis] + u[axis] * su + v[axis] * sv) * half);
+                vertices.push(MeshVertex::new(position, normal, uv));
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        Mesh::new(device, &vertices, &indices)
+    }
+    pub fn vertex_buffer(&self) -> &wgpu::Buffer {
+        &self.inner.vertex_buffer
+    }
+    pub fn index_buffer(&self) -> &wgpu::Buffer {
+        &self.inner.index_buffer
+    }
+    pub fn index_count(&self) -> u32 {
+        self.inner.index_count
+    }
+    pub fn ptr_eq(&self, other: &Mesh) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Material {
+    pub fn new(base_color: wgpu::Color) -> Material {
+        Material {
+            base_color,
+            texture: None,
+        }
+    }
+    pub fn texture(mut self, texture: &Texture) -> Self {
+        self.texture = Some(texture.clone());
+        self
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::new(wgpu::Color::WHITE)
+    }
+}
+
+impl DirectionalLight {
+    pub fn new(direction: [f32; 3], color: wgpu::Color) -> DirectionalLight {
+        DirectionalLight {
+            direction: normalize(direction),
+            color,
+        }
+    }
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        DirectionalLight::new([-0.4, -1.0, -0.6], wgpu::Color::WHITE)
+    }
+}
+
+impl MeshRenderer {
+    pub fn new(device: &ArcedRenderDevice) -> MeshRenderer {
+        let shader = device
+            .device
+            .create_shader_module(wgpu::include_wgsl!("../shaders/mesh.wgsl"));
+        let globals_bind_group_layout =
+            device
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Mesh Globals Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+        let texture_bind_group_layout = create_texture_bind_group_layout(device);
+        let pipeline_layout =
+            device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Mesh Pipeline Layout"),
+                    bind_group_layouts: &[&globals_bind_group_layout, &texture_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        MeshRenderer {
+            device: device.clone(),
+            shader,
+            globals_bind_group_layout,
+            texture_bind_group_layout,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            sampler: TextureSampler::new(device),
+            bind_groups: HashMap::new(),
+            depth: None,
+            white: Texture::create_rgba8(device, 1, 1, &[255; 4], &TextureOptions::default()),
+            camera: Camera3D::perspective(std::f32::consts::FRAC_PI_4, 1.0, 0.1, 100.0),
+            light: DirectionalLight::default(),
+            ambient: wgpu::Color {
+                r: 0.1,
+                g: 0.1,
+                b: 0.1,
+                a: 1.0,
+            },
+            draws: Vec::new(),
+        }
+    }
+    pub fn set_sampler(&mut self, sampler: TextureSampler) {
+        self.sampler = sampler;
+    }
+    pub fn set_camera(&mut self, camera: Camera3D) {
+        self.camera = camera;
+    }
+    pub const fn camera(&self) -> &Camera3D {
+        &self.camera
+    }
+    pub fn camera_mut(&mut self) -> &mut Camera3D {
+        &mut self.camera
+    }
+    pub fn set_light(&mut self, light: DirectionalLight) {
+        self.light = light;
+    }
+    pub const fn light(&self) -> &DirectionalLight {
+        &self.light
+    }
+    pub fn set_ambient(&mut self, ambient: wgpu::Color) {
+        self.ambient = ambient;
+    }
+    pub const fn ambient(&self) -> wgpu::Color {
+        self.ambient
+    }
+    pub fn clear(&mut self) {
+        self.draws.clear();
+    }
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+    pub fn draw(&mut self, mesh: &Mesh, material: &Material, transform: Mat4) {
+        self.draws.push(MeshDraw {
+            mesh: mesh.clone(),
+            texture: material.texture.clone(),
+            instance: MeshInstance {
+                model: transform.cols,
+                normal: transform.normal_matrix(),
+                color: color_to_array(material.base_color),
+            },
+        });
+    }
+    pub fn flush(&mut self, target: &RenderableTexture) {
+        let mut encoder =
+            self.device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Mesh Command Encoder"),
+                });
+        self.flush_with_encoder(&mut encoder, target);
+        self.device.queue.submit([encoder.finish()]);
+    }
+    pub fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        self.camera.set_aspect(target.width(), target.height());
+        let globals = MeshGlobals {
+            view_projection: self.camera.view_projection().cols,
+            light_direction: {
+                let [x, y, z] = normalize(self.light.direction);
+                [x, y, z, 0.0]
+            },
+            light_color: color_to_array(self.light.color),
+            ambient: color_to_array(self.ambient),
+        };
+        let globals_buffer = self
+            .device
+            .device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Mesh Globals Buffer"),
+                contents: bytemuck::bytes_of(&globals),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let globals_bind_group = self
+            .device
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mesh Globals Bind Group"),
+                layout: &self.globals_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: globals_buffer.as_entire_binding(),
+                }],
+            });
+        let instances = self
+            .draws
+            .iter()
+            .map(|draw| draw.instance)
+            .collect::<Vec<_>>();
+        let instance_buffer = self
+            .device
+            .device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Mesh Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let mut bind_groups = HashMap::new();
+        for draw in &self.draws {
+            let texture = draw.texture.as_ref().unwrap_or(&self.white);
+            let id = texture.wgpu_texture().global_id();
+            bind_groups.entry(id).or_insert_with(|| {
+                self.bind_groups.remove(&id).unwrap_or_else(|| {
+                    create_texture_bind_group(
+                        &self.device,
+                        &self.texture_bind_group_layout,
+                        texture.view(),
+                        &self.sampler,
+                    )
+                })
+            });
+        }
+        self.bind_groups = bind_groups;
+        let view = target.create_view();
+        let multisampled_view = target.create_multisampled_view();
+        let (depth_view, depth_format, depth_load) =
+            match (target.create_depth_view(), target.depth_format()) {
+                (Some(depth_view), Some(format)) => (depth_view, format, wgpu::LoadOp::Load),
+                _ => (
+                    self.depth_view(target),
+                    DEPTH_FORMAT,
+                    wgpu::LoadOp::Clear(1.0),
+                ),
+            };
+        let key = (target.format(), depth_format, target.sample_count());
+        self.ensure_pipeline(key);
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mesh Render Pass"),
+                color_attachments: &[Some(target.color_attachment(
+                    &view,
+                    multisampled_view.as_ref(),
+                    wgpu::LoadOp::Load,
+                ))],
+                depth_stencil_attachment: Some(depth_stencil_attachment(
+                    &depth_view,
+                    depth_format,
+                    depth_load,
+                    wgpu::LoadOp::Load,
+                )),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.pipelines[&key]);
+            render_pass.set_bind_group(0, &globals_bind_group, &[]);
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            for (index, draw) in self.draws.iter().enumerate() {
+                let index = index as u32;
+                let texture = draw.texture.as_ref().unwrap_or(&self.white);
+                render_pass.set_bind_group(
+                    1,
+                    &self.bind_groups[&texture.wgpu_texture().global_id()],
+                    &[],
+                );
+                render_pass.set_vertex_buffer(0, draw.mesh.vertex_buffer().slice(..));
+                render_pass.set_index_buffer(
+                    draw.mesh.index_buffer().slice(..),
+                    wgpu::IndexFormat::Uint32,
+                );
+                render_pass.draw_indexed(0..draw.mesh.index_count(), 0, index..index + 1);
+            }
+        }
+
+        self.clear();
+    }
+    fn depth_view(&mut self, target: &RenderableTexture) -> wgpu::TextureView {
+        let size = target.wgpu_texture().size();
+        let sample_count = target.sample_count();
+        let depth = match self.depth.take() {
+            Some(depth) if depth.size() == size && depth.sample_count() == sample_count => depth,
+            _ => self.device.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Mesh Depth Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            }),
+        };
+        let view = depth.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Mesh Depth View"),
+            ..Default::default()
+        });
+        self.depth = Some(depth);
+        view
+    }
+    fn ensure_pipeline(&mut self, key: PipelineKey) {
+        if self.pipelines.contains_key(&key) {
+            return;
+        }
+        let (format, depth_format, sample_count) = key;
+        let pipeline = self
+            .device
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Mesh Render Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader,
+                    entry_point: "vs_main",
+                    buffers: &[MeshVertex::layout(), MeshInstance::layout()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: StencilMode::Disabled.depth_stencil_state(Some(depth_format)),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+        self.pipelines.insert(key, pipeline);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RenderInstance;
+
+    #[test]
+    fn flush_without_depth_attachment() {
+        let Ok(device) = RenderInstance::new().headless_device() else {
+            return;
+        };
+        let target = RenderableTexture::new(&device, 64, 32, wgpu::TextureFormat::Rgba8UnormSrgb);
+        let mut renderer = MeshRenderer::new(&device);
+        let mesh = Mesh::cube(&device, 1.0);
+        renderer.draw(&mesh, &Material::default(), Mat4::IDENTITY);
+        renderer.draw(&mesh, &Material::default(), Mat4::IDENTITY);
+
+        let ((), error) = device.validation_scope(|| renderer.flush(&target));
+        assert!(error.is_none(), "{:?}", error);
+        assert_eq!(renderer.camera().aspect, 2.0);
+        assert_eq!(renderer.bind_groups.len(), 1);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::str::SplitWhitespace;
+
+use super::MeshVertex;
+use crate::math::{cross, normalize, sub};
+use crate::{Error, Result};
+
+type VertexKey = (usize, Option<usize>, Option<usize>);
+
+#[derive(Default)]
+struct ObjParser {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    uvs: Vec<[f32; 2]>,
+    normals: Vec<[f32; 3]>,
+    vertices: Vec<MeshVertex>,
+    indices: Vec<u32>,
+    lookup: HashMap<VertexKey, u32>,
+    missing_normals: bool,
+}
+
+pub(crate) fn parse(source: &str) -> Result<(Vec<MeshVertex>, Vec<u32>)> {
+    let mut parser = ObjParser::default();
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        parser.parse_line(line).map_err(|message| Error::ParseObj {
+            line: number + 1,
+            message,
+        })?;
+    }
+    if parser.missing_normals {
+        parser.generate_normals();
+    }
+    Ok((parser.vertices, parser.indices))
+}
+
+impl ObjParser {
+    fn parse_line(&mut self, line: &str) -> std::result::Result<(), String> {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let values = parse_floats(tokens)?;
+                match values.len() {
+                    3 | 4 => {
+                        self.positions.push([values[0], values[1], values[2]]);
+                        self.colors.push([1.0; 4]);
+                    }
+                    6 | 7 => {
+                        self.positions.push([values[0], values[1], values[2]]);
+                        self.colors.push([values[3], values[4], values[5], 1.0]);
+                    }
+                    _ => return Err(String::from("expected 3, 4, 6 or 7 values for a vertex")),
+                }
+            }
+            Some("vt") => {
+                let values = parse_floats(tokens)?;
+                match values.len() {
+                    1 => self.uvs.push([values[0], 1.0]),
+                    2 | 3 => self.uvs.push([values[0], 1.0 - values[1]]),
+                    _ => {
+                        return Err(String::from(
+                            "expected 1, 2 or 3 values for a texture coordinate",
+                        ))
+                    }
+                }
+            }
+            Some("vn") => {
+                let values = parse_floats(tokens)?;
+                if values.len() != 3 {
+                    return Err(String::from("expected 3 values for a normal"));
+                }
+                self.normals.push([values[0], values[1], values[2]]);
+            }
+            Some("f") => {
+                let corners = tokens
+                    .map(|corner| self.parse_corner(corner))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return Err(String::from("a face needs at least 3 vertices"));
+                }
+                for i in 1..corners.len() - 1 {
+                    self.indices
+                        .extend_from_slice(&[corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+    fn parse_corner(&mut self, corner: &str) -> std::result::Result<u32, String> {
+        let mut parts = corner.split('/');
+        let position = resolve_index(parts.next(), self.positions.len())?
+            .ok_or_else(|| format!("missing vertex index in '{}'", corner))?;
+        let uv = resolve_index(parts.next(), self.uvs.len())?;
+        let normal = resolve_index(parts.next(), self.normals.len())?;
+
+        let key = (position, uv, normal);
+        if let Some(&index) = self.lookup.get(&key) {
+            return Ok(index);
+        }
+        if normal.is_none() {
+            self.missing_normals = true;
+        }
+        let index = self.vertices.len() as u32;
+        self.vertices.push(MeshVertex {
+            position: self.positions[position],
+            normal: normal.map_or([0.0; 3], |normal| self.normals[normal]),
+            uv: uv.map_or([0.0; 2], |uv| self.uvs[uv]),
+            color: self.colors[position],
+        });
+        self.lookup.insert(key, index);
+        Ok(index)
+    }
+    fn generate_normals(&mut self) {
+        let generated = self
+            .lookup
+            .iter()
+            .filter(|(key, _)| key.2.is_none())
+            .map(|(_, &index)| index as usize)
+            .collect::<Vec<_>>();
+        let mut normals = vec![[0.0; 3]; self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize].position);
+            let normal = cross(sub(b, a), sub(c, a));
+            for &index in triangle {
+                let sum = &mut normals[index as usize];
+                for axis in 0..3 {
+                    sum[axis] += normal[axis];
+                }
+            }
+        }
+        for index in generated {
+            self.vertices[index].normal = normalize(normals[index]);
+        }
+    }
+}
+
+fn parse_floats(tokens: SplitWhitespace) -> std::result::Result<Vec<f32>, String> {
+    tokens
+        .map(|token| {
+            token
+                .parse::<f32>()
+                .map_err(|_| format!("invalid number '{}'", token))
+        })
+        .collect()
+}
+
+fn resolve_index(token: Option<&str>, len: usize) -> std::result::Result<Option<usize>, String> {
+    let token = match token {
+        Some(token) if !token.is_empty() => token,
+        _ => return Ok(None),
+    };
+    let index = token
+        .parse::<i64>()
+        .map_err(|_| format!("invalid index '{}'", token))?;
+    let resolved = if index < 0 {
+        len as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("index {} is out of range", index));
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+    const SQUARE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    fn parse_error(source: &str) -> (usize, String) {
+        match parse(source) {
+            Err(Error::ParseObj { line, message }) => (line, message),
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn parse_triangle() {
+        let (vertices, indices) = parse(&format!("{}f 1 2 3\n", TRIANGLE)).unwrap();
+        assert_eq!(indices, [0, 1, 2]);
+        assert_eq!(
+            vertices.iter().map(|v| v.position).collect::<Vec<_>>(),
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert!(vertices.iter().all(|v| v.color == [1.0; 4]));
+    }
+
+    #[test]
+    fn parse_quad_and_polygon_fans() {
+        let (vertices, indices) = parse(&format!("{}f 1 2 3 4\n", SQUARE)).unwrap();
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+
+        let pentagon = "v 0 0 0\nv 2 0 0\nv 3 1 0\nv 1 2 0\nv -1 1 0\nf 1 2 3 4 5\n";
+        let (vertices, indices) = parse(pentagon).unwrap();
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(indices, [0, 1, 2, 0, 2, 3, 0, 3, 4]);
+    }
+
+    #[test]
+    fn parse_negative_indices() {
+        let absolute = parse(&format!("{}f 1 2 3\n", TRIANGLE)).unwrap();
+        let relative = parse(&format!("{}f -3 -2 -1\n", TRIANGLE)).unwrap();
+        assert_eq!(absolute, relative);
+    }
+
+    #[test]
+    fn parse_shared_corners() {
+        let source = format!("{}f 1 2 3\nf 1 3 4\n", SQUARE);
+        let (vertices, indices) = parse(&source).unwrap();
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn parse_position_and_normal_corners() {
+        let source = format!("{}vn 0 0 -1\nf 1//1 2//1 3//1\n", TRIANGLE);
+        let (vertices, _) = parse(&source).unwrap();
+        assert!(vertices.iter().all(|v| v.normal == [0.0, 0.0, -1.0]));
+        assert!(vertices.iter().all(|v| v.uv == [0.0, 0.0]));
+    }
+
+    #[test]
+    fn parse_position_and_uv_corners() {
+        let source = format!("{}vt 0.25 0.75\nvt 1\nf 1/1 2/2 3/1\n", TRIANGLE);
+        let (vertices, _) = parse(&source).unwrap();
+        assert_eq!(
+            vertices.iter().map(|v| v.uv).collect::<Vec<_>>(),
+            [[0.25, 0.25], [1.0, 1.0], [0.25, 0.25]]
+        );
+        assert!(vertices.iter().all(|v| v.normal == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn generated_normals_are_averaged() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 2 3\nf 1 4 2\n";
+        let (vertices, _) = parse(source).unwrap();
+        let shared = 0.5f32.sqrt();
+        for (vertex, normal) in vertices.iter().zip([
+            [0.0, shared, shared],
+            [0.0, shared, shared],
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0],
+        ]) {
+            for (actual, expected) in vertex.normal.iter().zip(normal) {
+                assert!((actual - expected).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_vertex_colors_and_comments() {
+        let source = "# colored\nv 0 0 0 1 0 0\nv 1 0 0 0 1 0 1\nv 0 1 0 1 # w\nf 1 2 3\n";
+        let (vertices, _) = parse(source).unwrap();
+        assert_eq!(
+            vertices.iter().map(|v| v.color).collect::<Vec<_>>(),
+            [[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [1.0; 4]]
+        );
+    }
+
+    #[test]
+    fn out_of_range_indices() {
+        let error = |face: &str| parse_error(&format!("{}# faces\n{}\n", TRIANGLE, face));
+        assert_eq!(
+            error("f 1 2 4"),
+            (5, String::from("index 4 is out of range"))
+        );
+        assert_eq!(
+            error("f 0 1 2"),
+            (5, String::from("index 0 is out of range"))
+        );
+        assert_eq!(
+            error("f -4 1 2"),
+            (5, String::from("index -4 is out of range"))
+        );
+        assert_eq!(
+            error("f 1/1 2/1 3/1"),
+            (5, String::from("index 1 is out of range"))
+        );
+        assert_eq!(
+            error("f 1//1 2//1 3//1"),
+            (5, String::from("index 1 is out of range"))
+        );
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 x 0\n"),
+            (2, String::from("invalid number 'x'"))
+        );
+        assert_eq!(
+            parse_error("vt 0.5 half\n"),
+            (1, String::from("invalid number 'half'"))
+        );
+        assert_eq!(
+            parse_error("\nvn 0 0 1e\n"),
+            (2, String::from("invalid number '1e'"))
+        );
+        assert_eq!(
+            parse_error(&format!("{}f 1 a 3\n", TRIANGLE)),
+            (4, String::from("invalid index 'a'"))
+        );
+        assert_eq!(
+            parse_error(&format!("{}f /1 2 3\n", TRIANGLE)),
+            (4, String::from("missing vertex index in '/1'"))
+        );
+    }
+
+    #[test]
+    fn wrong_value_counts() {
+        assert_eq!(
+            parse_error("v 0 0\n"),
+            (1, String::from("expected 3, 4, 6 or 7 values for a vertex"))
+        );
+        assert_eq!(
+            parse_error("v 0 0 0 1 1\n"),
+            (1, String::from("expected 3, 4, 6 or 7 values for a vertex"))
+        );
+        assert_eq!(
+            parse_error("vt\n"),
+            (
+                1,
+                String::from("expected 1, 2 or 3 values for a texture coordinate")
+            )
+        );
+        assert_eq!(
+            parse_error("vn 0 1\n"),
+            (1, String::from("expected 3 values for a normal"))
+        );
+    }
+
+    #[test]
+    fn short_faces() {
+        assert_eq!(
+            parse_error(&format!("{}f 1 2\n", TRIANGLE)),
+            (4, String::from("a face needs at least 3 vertices"))
+        );
+        assert_eq!(
+            parse_error(&format!("{}\nf\n", TRIANGLE)),
+            (5, String::from("a face needs at least 3 vertices"))
+        );
+    }
+}
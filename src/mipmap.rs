@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::texture::{create_texture_bind_group, create_texture_bind_group_layout, TextureSampler};
+use crate::RenderDevice;
+
+pub struct MipmapGenerator {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: TextureSampler,
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl MipmapGenerator {
+    pub fn new(device: &RenderDevice) -> MipmapGenerator {
+        let shader = device
+            .device
+            .create_shader_module(wgpu::include_wgsl!("shaders/blit.wgsl"));
+        let bind_group_layout = create_texture_bind_group_layout(device);
+        let pipeline_layout =
+            device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Mipmap Pipeline Layout"),
+                    bind_group_layouts: &[&bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        MipmapGenerator {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            sampler: TextureSampler::linear(device),
+            pipelines: HashMap::new(),
+        }
+    }
+    pub fn generate(
+        &mut self,
+        device: &RenderDevice,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        let level_count = texture.mip_level_count();
+        if level_count < 2 {
+            return;
+        }
+
+        let format = texture.format();
+        self.ensure_pipeline(device, format);
+        let pipeline = &self.pipelines[&format];
+        let views = (0..level_count)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mipmap Level View"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for level in 1..level_count as usize {
+            let bind_group = create_texture_bind_group(
+                device,
+                &self.bind_group_layout,
+                &views[level - 1],
+                &self.sampler,
+            );
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &views[level],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+    fn ensure_pipeline(&mut self, device: &RenderDevice, format: wgpu::TextureFormat) {
+        if self.pipelines.contains_key(&format) {
+            return;
+        }
+        let pipeline = device
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Mipmap Render Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+        self.pipelines.insert(format, pipeline);
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Mutex;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::texture::RenderableTexture;
+use crate::{ArcedRenderDevice, Error, Result};
+
+pub struct PixelBuffer {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    dirty_rows: Mutex<Option<(u32, u32)>>,
+}
+
+impl PixelBuffer {
+    pub fn new(width: u32, height: u32, format: wgpu::TextureFormat) -> Result<PixelBuffer> {
+        if !is_supported_format(format) {
+            return Err(Error::UnsupportedFormat(format));
+        }
+
+        Ok(PixelBuffer {
+            data: vec![255; (width * height * 4) as usize],
+            width,
+            height,
+            format,
+            dirty_rows: Mutex::new(None),
+        })
+    }
+    pub fn frame(&self) -> &[u8] {
+        &self.data
+    }
+    pub fn frame_mut(&mut self) -> &mut [u8] {
+        self.mark_rows_dirty(0, self.height);
+        &mut self.data
+    }
+    pub fn rows_mut(&mut self, start: u32, end: u32) -> &mut [u8] {
+        let end = end.min(self.height);
+        self.mark_rows_dirty(start, end);
+        let stride = (self.width * 4) as usize;
+        &mut self.data[start as usize * stride..end as usize * stride]
+    }
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        let index = self.index(x, y)?;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.data[index..index + 4]);
+        Some(pixel)
+    }
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if let Some(index) = self.index(x, y) {
+            self.data[index..index + 4].copy_from_slice(&rgba);
+            self.mark_rows_dirty(y, y + 1);
+        }
+    }
+    pub fn fill(&mut self, rgba: [u8; 4]) {
+        for pixel in self.frame_mut().chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+    pub fn mark_rows_dirty(&self, start: u32, end: u32) {
+        if start >= end {
+            return;
+        }
+        let mut dirty_rows = self.dirty_rows.lock().unwrap();
+        *dirty_rows = Some(match *dirty_rows {
+            Some((dirty_start, dirty_end)) => (dirty_start.min(start), dirty_end.max(end)),
+            None => (start, end),
+        });
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_rows.lock().unwrap().is_some()
+    }
+    pub fn upload(&self, device: &ArcedRenderDevice, texture: &RenderableTexture) -> Result<()> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+        let mut encoder = device
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Pixel Upload Command Encoder"),
+            });
+        self.upload_with_encoder(device, &mut encoder, texture)?;
+        device.queue.submit([encoder.finish()]);
+        Ok(())
+    }
+    pub fn upload_with_encoder(
+        &self,
+        device: &ArcedRenderDevice,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &RenderableTexture,
+    ) -> Result<()> {
+        if !is_supported_format(texture.format()) {
+            return Err(Error::UnsupportedFormat(texture.format()));
+        }
+        if self.width > texture.width() || self.height > texture.height() {
+            return Err(Error::RegionOutOfBounds);
+        }
+        self.record_upload(device, encoder, texture);
+        Ok(())
+    }
+    pub(crate) fn record_upload(
+        &self,
+        device: &ArcedRenderDevice,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &RenderableTexture,
+    ) {
+        let Some((start, end)) = self.dirty_rows.lock().unwrap().take() else {
+            return;
+        };
+        let swizzle = matches!(
+            texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let stride = (self.width * 4) as usize;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+        let padded_stride = stride.next_multiple_of(alignment);
+        let mut staging = vec![0; padded_stride * (end - start) as usize];
+        let rows = self.data[start as usize * stride..end as usize * stride].chunks_exact(stride);
+        for (row, padded) in rows.zip(staging.chunks_exact_mut(padded_stride)) {
+            padded[..stride].copy_from_slice(row);
+            if swizzle {
+                for pixel in padded[..stride].chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+            }
+        }
+        let buffer = device.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Pixel Staging Buffer"),
+            contents: &staging,
+            usage: wgpu::BufferUsages::COPY_SRC,
+        });
+
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_stride as u32),
+                    rows_per_image: Some(end - start),
+                },
+            },
+            wgpu::ImageCopyTexture {
+                texture: texture.wgpu_texture(),
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: start,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: end - start,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+    pub const fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(((y * self.width + x) * 4) as usize)
+    }
+}
+
+impl Clone for PixelBuffer {
+    fn clone(&self) -> Self {
+        PixelBuffer {
+            data: self.data.clone(),
+            width: self.width,
+            height: self.height,
+            format: self.format,
+            dirty_rows: Mutex::new(*self.dirty_rows.lock().unwrap()),
+        }
+    }
+}
+
+fn is_supported_format(format: wgpu::TextureFormat) -> bool {
+    matches!(
+        format,
+        wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_formats_without_8_bit_channels() {
+        for format in [
+            wgpu::TextureFormat::Rgba16Float,
+            wgpu::TextureFormat::Rgba32Float,
+            wgpu::TextureFormat::Rgb10a2Unorm,
+            wgpu::TextureFormat::R8Unorm,
+        ] {
+            assert!(matches!(
+                PixelBuffer::new(4, 4, format),
+                Err(Error::UnsupportedFormat(unsupported)) if unsupported == format
+            ));
+        }
+        assert!(PixelBuffer::new(4, 4, wgpu::TextureFormat::Bgra8UnormSrgb).is_ok());
+    }
+
+    #[test]
+    fn starts_white_and_clean() {
+        let pixels = PixelBuffer::new(2, 2, wgpu::TextureFormat::Rgba8Unorm).unwrap();
+        assert!(pixels.frame().iter().all(|&byte| byte == 255));
+        assert!(!pixels.is_dirty());
+    }
+
+    #[test]
+    fn tracks_dirty_rows() {
+        let mut pixels = PixelBuffer::new(4, 8, wgpu::TextureFormat::Rgba8Unorm).unwrap();
+        pixels.set_pixel(1, 5, [1, 2, 3, 4]);
+        pixels.set_pixel(0, 2, [5, 6, 7, 8]);
+        pixels.set_pixel(9, 9, [0; 4]);
+        assert_eq!(*pixels.dirty_rows.lock().unwrap(), Some((2, 6)));
+        assert_eq!(pixels.get_pixel(1, 5), Some([1, 2, 3, 4]));
+        assert_eq!(pixels.get_pixel(4, 0), None);
+        assert_eq!(pixels.rows_mut(6, 20).len(), 2 * 4 * 4);
+        assert_eq!(*pixels.dirty_rows.lock().unwrap(), Some((2, 8)));
+    }
+}
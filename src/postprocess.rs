@@ -0,0 +1,471 @@
+use std::time::Instant;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::shader::{create_shader_module, validate, ShaderSource};
+use crate::texture::{
+    create_texture_bind_group, create_texture_bind_group_layout, RenderableTexture, Texture,
+    TextureBindGroupCache, TextureSampler,
+};
+use crate::{ArcedRenderDevice, Error, Result};
+
+const PRELUDE: &str = include_str!("shaders/post/prelude.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostGlobal {
+    resolution: [f32; 2],
+    time: f32,
+    padding: f32,
+}
+
+#[derive(Clone)]
+pub struct PostEffect {
+    source: ShaderSource,
+    params: Option<Vec<u8>>,
+    texture: Option<Texture>,
+}
+
+struct CompiledEffect {
+    effect: PostEffect,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: Option<wgpu::RenderPipeline>,
+    uniform_bind_group: wgpu::BindGroup,
+    texture_bind_group: Option<wgpu::BindGroup>,
+    params_buffer: Option<wgpu::Buffer>,
+    input_bind_group: TextureBindGroupCache,
+    needs_texture: bool,
+    enabled: bool,
+}
+
+pub struct PostProcessChain {
+    device: ArcedRenderDevice,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    global_bind_group_layout: wgpu::BindGroupLayout,
+    params_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: TextureSampler,
+    global_buffer: wgpu::Buffer,
+    effects: Vec<CompiledEffect>,
+    targets: Option<[RenderableTexture; 2]>,
+    start: Instant,
+}
+
+impl PostEffect {
+    pub fn new(source: impl Into<ShaderSource>) -> PostEffect {
+        PostEffect {
+            source: source.into(),
+            params: None,
+            texture: None,
+        }
+    }
+    pub fn params<T: bytemuck::Pod>(mut self, params: &T) -> Self {
+        self.params = Some(bytemuck::bytes_of(params).to_vec());
+        self
+    }
+    pub fn texture(mut self, texture: Texture) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+    pub fn grayscale(amount: f32) -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/grayscale.wgsl"))
+            .params(&[amount, 0.0, 0.0, 0.0])
+    }
+    pub fn vignette(strength: f32, radius: f32, softness: f32) -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/vignette.wgsl"))
+            .params(&[strength, radius, softness, 0.0])
+    }
+    pub fn crt(scanline_intensity: f32, curvature: f32) -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/crt.wgsl")).params(&[
+            scanline_intensity,
+            curvature,
+            0.0,
+            0.0,
+        ])
+    }
+    pub fn bloom(threshold: f32, intensity: f32, radius: f32) -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/bloom.wgsl"))
+            .params(&[threshold, intensity, radius, 0.0])
+    }
+    pub fn tonemap(exposure: f32, gamma: f32) -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/tonemap.wgsl"))
+            .params(&[exposure, gamma, 0.0, 0.0])
+    }
+    pub fn fxaa() -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/fxaa.wgsl"))
+    }
+    pub fn color_grading(lut: Texture, intensity: f32) -> PostEffect {
+        PostEffect::new(include_str!("shaders/post/color_grading.wgsl"))
+            .params(&[intensity, 0.0, 0.0, 0.0])
+            .texture(lut)
+    }
+}
+
+impl CompiledEffect {
+    fn is_active(&self) -> bool {
+        self.enabled && self.pipeline.is_some()
+    }
+}
+
+impl PostProcessChain {
+    pub fn new(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> PostProcessChain {
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let global_bind_group_layout =
+            device
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Post Process Global Bind Group Layout"),
+                    entries: &[uniform_entry(0)],
+                });
+        let params_bind_group_layout =
+            device
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Post Process Params Bind Group Layout"),
+                    entries: &[uniform_entry(0), uniform_entry(1)],
+                });
+        let global_buffer = device.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Process Global Buffer"),
+            size: std::mem::size_of::<PostGlobal>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        PostProcessChain {
+            device: device.clone(),
+            format,
+            size: (width, height),
+            texture_bind_group_layout: create_texture_bind_group_layout(device),
+            global_bind_group_layout,
+            params_bind_group_layout,
+            sampler: TextureSampler::linear(device),
+            global_buffer,
+            effects: Vec::new(),
+            targets: None,
+            start: Instant::now(),
+        }
+    }
+    pub fn recreate(&self, device: &ArcedRenderDevice) -> PostProcessChain {
+        let (width, height) = self.size;
+        let mut chain = PostProcessChain::new(device, width, height, self.format);
+        chain.start = self.start;
+        for effect in &self.effects {
+            let needs_texture = effect.needs_texture || effect.effect.texture.is_some();
+            let mut compiled = chain.create_effect(PostEffect {
+                texture: None,
+                ..effect.effect.clone()
+            });
+            if !needs_texture {
+                compiled.pipeline = chain
+                    .compile(&compiled.pipeline_layout, compiled.effect.source.source())
+                    .ok();
+            }
+            compiled.needs_texture = needs_texture;
+            compiled.enabled = effect.enabled;
+            chain.effects.push(compiled);
+        }
+        if !chain.effects.is_empty() {
+            chain.targets = Some(chain.create_targets());
+        }
+        chain
+    }
+    pub fn push(&mut self, effect: PostEffect) -> Result<usize> {
+        let effect = self.compile_effect(effect)?;
+        if self.targets.is_none() {
+            self.targets = Some(self.create_targets());
+        }
+        self.effects.push(effect);
+        Ok(self.effects.len() - 1)
+    }
+    pub fn reload(&mut self) -> Result<bool> {
+        let mut changed = false;
+        let mut first_error = None;
+        for index in 0..self.effects.len() {
+            let effect = &mut self.effects[index];
+            let needs_texture = effect.needs_texture;
+            let result = effect.effect.source.poll_changed().and_then(|modified| {
+                if !modified || needs_texture {
+                    return Ok(None);
+                }
+                let effect = &self.effects[index];
+                self.compile(&effect.pipeline_layout, effect.effect.source.source())
+                    .map(Some)
+            });
+            match result {
+                Ok(Some(pipeline)) => {
+                    self.effects[index].pipeline = Some(pipeline);
+                    changed = true;
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(changed),
+        }
+    }
+    pub fn remove(&mut self, index: usize) {
+        self.effects.remove(index);
+    }
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.effects[index].enabled = enabled;
+    }
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.effects[index].enabled
+    }
+    pub fn set_texture(&mut self, index: usize, texture: Texture) -> Result<()> {
+        let mut effect = self.compile_effect(PostEffect {
+            texture: Some(texture),
+            ..self.effects[index].effect.clone()
+        })?;
+        effect.enabled = self.effects[index].enabled;
+        self.effects[index] = effect;
+        Ok(())
+    }
+    pub fn needs_texture(&self, index: usize) -> bool {
+        self.effects[index].needs_texture
+    }
+    pub fn set_params<T: bytemuck::Pod>(&mut self, index: usize, params: &T) -> Result<()> {
+        let bytes = bytemuck::bytes_of(params);
+        let effect = &mut self.effects[index];
+        let limit = effect
+            .params_buffer
+            .as_ref()
+            .map_or(0, |buffer| buffer.size());
+        if bytes.len() as u64 > limit {
+            return Err(Error::ParamsTooLarge {
+                size: bytes.len(),
+                limit,
+            });
+        }
+        if let (Some(buffer), Some(stored)) = (&effect.params_buffer, &mut effect.effect.params) {
+            self.device.queue.write_buffer(buffer, 0, bytes);
+            if stored.len() < bytes.len() {
+                stored.resize(bytes.len(), 0);
+            }
+            stored[..bytes.len()].copy_from_slice(bytes);
+        }
+        effect.input_bind_group.clear();
+        Ok(())
+    }
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 || self.size == (width, height) {
+            return;
+        }
+        self.size = (width, height);
+        if self.targets.is_some() {
+            self.targets = Some(self.create_targets());
+        }
+        for effect in &mut self.effects {
+            effect.input_bind_group.clear();
+        }
+    }
+    pub fn run<'a>(
+        &'a self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &'a RenderableTexture,
+    ) -> Option<&'a RenderableTexture> {
+        let targets = self.targets.as_ref()?;
+        if !self.effects.iter().any(CompiledEffect::is_active) {
+            return None;
+        }
+
+        let global = PostGlobal {
+            resolution: [self.size.0 as f32, self.size.1 as f32],
+            time: self.start.elapsed().as_secs_f32(),
+            padding: 0.0,
+        };
+        self.device
+            .queue
+            .write_buffer(&self.global_buffer, 0, bytemuck::bytes_of(&global));
+
+        let mut input = source;
+        let mut output_index = 0;
+        for effect in &self.effects {
+            let (true, Some(pipeline)) = (effect.enabled, &effect.pipeline) else {
+                continue;
+            };
+            let output = &targets[output_index];
+            let view = output.create_view();
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Post Process Render Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(pipeline);
+                effect
+                    .input_bind_group
+                    .bind(&mut render_pass, 0, input, || {
+                        create_texture_bind_group(
+                            &self.device,
+                            &self.texture_bind_group_layout,
+                            &input.create_sampled_view(),
+                            &self.sampler,
+                        )
+                    });
+                render_pass.set_bind_group(1, &effect.uniform_bind_group, &[]);
+                if let Some(bind_group) = &effect.texture_bind_group {
+                    render_pass.set_bind_group(2, bind_group, &[]);
+                }
+                render_pass.draw(0..3, 0..1);
+            }
+            input = output;
+            output_index = 1 - output_index;
+        }
+
+        Some(input)
+    }
+    fn create_effect(&self, effect: PostEffect) -> CompiledEffect {
+        let params_buffer = effect.params.as_ref().map(|params| {
+            let mut contents = params.clone();
+            contents.resize(params.len().next_multiple_of(16).max(16), 0);
+            self.device
+                .device
+                .create_buffer_init(&BufferInitDescriptor {
+                    label: Some("Post Process Params Buffer"),
+                    contents: &contents,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+        });
+        let uniform_bind_group_layout = if params_buffer.is_some() {
+            &self.params_bind_group_layout
+        } else {
+            &self.global_bind_group_layout
+        };
+        let mut uniform_entries = vec![wgpu::BindGroupEntry {
+            binding: 0,
+            resource: self.global_buffer.as_entire_binding(),
+        }];
+        if let Some(buffer) = &params_buffer {
+            uniform_entries.push(wgpu::BindGroupEntry {
+                binding: 1,
+                resource: buffer.as_entire_binding(),
+            });
+        }
+        let uniform_bind_group = self
+            .device
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post Process Uniform Bind Group"),
+                layout: uniform_bind_group_layout,
+                entries: &uniform_entries,
+            });
+        let texture_bind_group = effect.texture.as_ref().map(|texture| {
+            create_texture_bind_group(
+                &self.device,
+                &self.texture_bind_group_layout,
+                texture.view(),
+                &self.sampler,
+            )
+        });
+
+        let mut bind_group_layouts =
+            vec![&self.texture_bind_group_layout, uniform_bind_group_layout];
+        if texture_bind_group.is_some() {
+            bind_group_layouts.push(&self.texture_bind_group_layout);
+        }
+        let pipeline_layout =
+            self.device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Post Process Pipeline Layout"),
+                    bind_group_layouts: &bind_group_layouts,
+                    push_constant_ranges: &[],
+                });
+
+        CompiledEffect {
+            effect,
+            pipeline_layout,
+            pipeline: None,
+            uniform_bind_group,
+            texture_bind_group,
+            params_buffer,
+            input_bind_group: TextureBindGroupCache::default(),
+            needs_texture: false,
+            enabled: true,
+        }
+    }
+    fn compile_effect(&self, effect: PostEffect) -> Result<CompiledEffect> {
+        let mut effect = self.create_effect(effect);
+        effect.pipeline =
+            Some(self.compile(&effect.pipeline_layout, effect.effect.source.source())?);
+        Ok(effect)
+    }
+    fn compile(&self, layout: &wgpu::PipelineLayout, source: &str) -> Result<wgpu::RenderPipeline> {
+        let shader = create_shader_module(&self.device, "Post Process Shader", PRELUDE, source)?;
+        validate(&self.device, || {
+            self.device
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Post Process Render Pipeline"),
+                    layout: Some(layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: self.format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                    cache: None,
+                })
+        })
+    }
+    fn create_targets(&self) -> [RenderableTexture; 2] {
+        let (width, height) = self.size;
+        [
+            RenderableTexture::new(&self.device, width, height, self.format),
+            RenderableTexture::new(&self.device, width, height, self.format),
+        ]
+    }
+}
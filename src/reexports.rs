@@ -1,3 +1,3 @@
 pub use bytemuck;
 pub use wgpu;
-pub use winit;
\ No newline at end of file
+pub use winit;
@@ -0,0 +1,142 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingMode {
+    #[default]
+    Stretch,
+    Fit,
+    Integer,
+    Crop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ScalingMode {
+    pub fn viewport(self, source: (u32, u32), target: (u32, u32)) -> Viewport {
+        let (source_width, source_height) = (source.0 as f32, source.1 as f32);
+        let (target_width, target_height) = (target.0 as f32, target.1 as f32);
+        let scale_x = target_width / source_width;
+        let scale_y = target_height / source_height;
+
+        let scale = match self {
+            ScalingMode::Stretch => {
+                return Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: target_width,
+                    height: target_height,
+                }
+            }
+            ScalingMode::Fit => scale_x.min(scale_y),
+            ScalingMode::Integer => {
+                let fit = scale_x.min(scale_y);
+                if fit < 1.0 {
+                    fit
+                } else {
+                    fit.floor()
+                }
+            }
+            ScalingMode::Crop => scale_x.max(scale_y),
+        };
+
+        let width = source_width * scale;
+        let height = source_height * scale;
+        Viewport {
+            x: ((target_width - width) / 2.0).floor(),
+            y: ((target_height - height) / 2.0).floor(),
+            width,
+            height,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+    pub fn to_source(&self, source: (u32, u32), x: f32, y: f32) -> Option<(u32, u32)> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let u = (x - self.x) / self.width;
+        let v = (y - self.y) / self.height;
+        Some((
+            ((u * source.0 as f32) as u32).min(source.0 - 1),
+            ((v * source.1 as f32) as u32).min(source.1 - 1),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn stretch_fills_target() {
+        assert_eq!(
+            ScalingMode::Stretch.viewport((320, 180), (800, 600)),
+            viewport(0.0, 0.0, 800.0, 600.0)
+        );
+    }
+
+    #[test]
+    fn fit_letterboxes() {
+        assert_eq!(
+            ScalingMode::Fit.viewport((320, 180), (800, 600)),
+            viewport(0.0, 75.0, 800.0, 450.0)
+        );
+        assert_eq!(
+            ScalingMode::Fit.viewport((100, 100), (101, 50)),
+            viewport(25.0, 0.0, 50.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn integer_uses_whole_scales() {
+        assert_eq!(
+            ScalingMode::Integer.viewport((320, 180), (800, 600)),
+            viewport(80.0, 120.0, 640.0, 360.0)
+        );
+        assert_eq!(
+            ScalingMode::Integer.viewport((320, 180), (160, 90)),
+            viewport(0.0, 0.0, 160.0, 90.0)
+        );
+    }
+
+    #[test]
+    fn crop_covers_target() {
+        let crop = ScalingMode::Crop.viewport((320, 180), (800, 600));
+        assert_eq!((crop.x, crop.y, crop.height), (-134.0, 0.0, 600.0));
+        assert!((crop.width - 3200.0 / 3.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn to_source_maps_inside_viewport() {
+        let viewport = ScalingMode::Integer.viewport((320, 180), (800, 600));
+        assert_eq!(viewport.to_source((320, 180), 80.0, 120.0), Some((0, 0)));
+        assert_eq!(
+            viewport.to_source((320, 180), 400.0, 300.0),
+            Some((160, 90))
+        );
+        assert_eq!(
+            viewport.to_source((320, 180), 719.9, 479.9),
+            Some((319, 179))
+        );
+        assert_eq!(viewport.to_source((320, 180), 79.0, 300.0), None);
+        assert_eq!(viewport.to_source((320, 180), 720.0, 300.0), None);
+        assert_eq!(viewport.to_source((320, 180), 400.0, 480.0), None);
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use pollster::FutureExt;
+
+use crate::{Error, RenderDevice, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShaderSource {
+    source: String,
+    watch: Option<ShaderWatch>,
+}
+
+#[derive(Debug, Clone)]
+struct ShaderWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ShaderSource {
+    pub fn embedded(source: impl Into<String>) -> ShaderSource {
+        ShaderSource {
+            source: source.into(),
+            watch: None,
+        }
+    }
+    pub fn from_path(path: impl AsRef<Path>) -> Result<ShaderSource> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+        let source = std::fs::read_to_string(&path)?;
+
+        Ok(ShaderSource {
+            source,
+            watch: Some(ShaderWatch { path, modified }),
+        })
+    }
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    pub fn path(&self) -> Option<&Path> {
+        self.watch.as_ref().map(|watch| watch.path.as_path())
+    }
+    pub fn poll_changed(&mut self) -> Result<bool> {
+        let watch = match &mut self.watch {
+            Some(watch) => watch,
+            None => return Ok(false),
+        };
+        let modified = modified_time(&watch.path);
+        if modified == watch.modified {
+            return Ok(false);
+        }
+        watch.modified = modified;
+        self.source = std::fs::read_to_string(&watch.path)?;
+        Ok(true)
+    }
+}
+
+impl From<&str> for ShaderSource {
+    fn from(source: &str) -> Self {
+        ShaderSource::embedded(source)
+    }
+}
+
+impl From<String> for ShaderSource {
+    fn from(source: String) -> Self {
+        ShaderSource::embedded(source)
+    }
+}
+
+impl fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+pub(crate) fn create_shader_module(
+    device: &RenderDevice,
+    label: &str,
+    prelude: &str,
+    source: &str,
+) -> Result<wgpu::ShaderModule> {
+    let prelude_lines = prelude.matches('\n').count() as u32;
+    device
+        .device
+        .push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = device
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(format!("{}{}", prelude, source).into()),
+        });
+    let info = module.get_compilation_info().block_on();
+    let scope_error = device.device.pop_error_scope().block_on();
+
+    let diagnostics = info
+        .messages
+        .into_iter()
+        .filter(|message| message.message_type == wgpu::CompilationMessageType::Error)
+        .map(|message| {
+            let (line, column) = message.location.map_or((0, 0), |location| {
+                (
+                    location.line_number.saturating_sub(prelude_lines),
+                    location.line_position,
+                )
+            });
+            ShaderDiagnostic {
+                message: message.message,
+                line,
+                column,
+            }
+        })
+        .collect::<Vec<_>>();
+    if !diagnostics.is_empty() {
+        return Err(Error::ShaderCompilation(diagnostics));
+    }
+    match scope_error {
+        Some(error) => Err(Error::ShaderValidation(error.to_string())),
+        None => Ok(module),
+    }
+}
+
+pub(crate) fn validate<T>(device: &RenderDevice, create: impl FnOnce() -> T) -> Result<T> {
+    match device.validation_scope(create) {
+        (_, Some(error)) => Err(Error::ShaderValidation(error.to_string())),
+        (value, None) => Ok(value),
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
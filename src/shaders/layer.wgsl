@@ -0,0 +1,38 @@
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> @builtin(position) vec4<f32> {
+    let x = f32(1 - i32(in_vertex_index)) * 10.;
+    let y = f32(i32(in_vertex_index & 1u) * 2 - 1) * 10.;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+struct LayerParams {
+    opacity: f32,
+    mode: u32,
+};
+
+@group(0) @binding(0)
+var layer_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: LayerParams;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = textureLoad(layer_texture, vec2<i32>(position.xy), 0);
+    let alpha = color.a * params.opacity;
+    switch params.mode {
+        case 1u, 3u: {
+            return vec4<f32>(color.rgb * alpha, 0.0);
+        }
+        case 2u: {
+            return vec4<f32>(mix(vec3<f32>(1.0), color.rgb, alpha), 0.0);
+        }
+        case 4u: {
+            return color * params.opacity;
+        }
+        default: {
+            return vec4<f32>(color.rgb, alpha);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+struct Globals {
+    view_projection: mat4x4<f32>,
+    light_direction: vec4<f32>,
+    light_color: vec4<f32>,
+    ambient: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+@group(1) @binding(0)
+var base_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var base_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) color: vec4<f32>,
+};
+
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+    @location(8) normal_0: vec3<f32>,
+    @location(9) normal_1: vec3<f32>,
+    @location(10) normal_2: vec3<f32>,
+    @location(11) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let normal = mat3x3<f32>(instance.normal_0, instance.normal_1, instance.normal_2);
+    var out: VertexOutput;
+    out.position = globals.view_projection * model * vec4<f32>(in.position, 1.0);
+    out.normal = normal * in.normal;
+    out.uv = in.uv;
+    out.color = in.color * instance.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(base_texture, base_sampler, in.uv) * in.color;
+    let diffuse = max(dot(normalize(in.normal), -globals.light_direction.xyz), 0.0);
+    let light = globals.ambient.rgb + globals.light_color.rgb * diffuse;
+    return vec4<f32>(base.rgb * light, base.a);
+}
@@ -0,0 +1,22 @@
+@group(1) @binding(1)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(input_texture, input_sampler, in.uv);
+    let texel = params.z / global.resolution;
+
+    var sum = vec3<f32>(0.0);
+    var weight_sum = 0.0;
+    for (var x = -3; x <= 3; x++) {
+        for (var y = -3; y <= 3; y++) {
+            let offset = vec2<f32>(f32(x), f32(y));
+            let weight = exp(-dot(offset, offset) / 8.0);
+            let sample = textureSampleLevel(input_texture, input_sampler, in.uv + offset * texel, 0.0).rgb;
+            sum += max(sample - vec3<f32>(params.x), vec3<f32>(0.0)) * weight;
+            weight_sum += weight;
+        }
+    }
+
+    return vec4<f32>(base.rgb + sum / weight_sum * params.y, base.a);
+}
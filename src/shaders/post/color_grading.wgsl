@@ -0,0 +1,25 @@
+@group(1) @binding(1)
+var<uniform> params: vec4<f32>;
+
+@group(2) @binding(0)
+var effect_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var effect_sampler: sampler;
+
+fn lut_uv(color: vec3<f32>, slice: f32, size: f32) -> vec2<f32> {
+    return vec2<f32>((slice * size + color.r + 0.5) / (size * size), (color.g + 0.5) / size);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let size = f32(textureDimensions(effect_texture).y);
+    let scaled = clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0)) * (size - 1.0);
+    let slice = floor(scaled.b);
+    let next_slice = min(slice + 1.0, size - 1.0);
+
+    let low = textureSampleLevel(effect_texture, effect_sampler, lut_uv(scaled, slice, size), 0.0).rgb;
+    let high = textureSampleLevel(effect_texture, effect_sampler, lut_uv(scaled, next_slice, size), 0.0).rgb;
+    let graded = mix(low, high, scaled.b - slice);
+    return vec4<f32>(mix(color.rgb, graded, params.x), color.a);
+}
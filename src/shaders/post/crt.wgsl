@@ -0,0 +1,14 @@
+@group(1) @binding(1)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var uv = in.uv * 2.0 - 1.0;
+    uv = uv + uv * (uv.yx * uv.yx) * params.y;
+    uv = uv * 0.5 + 0.5;
+
+    let color = textureSample(input_texture, input_sampler, uv).rgb;
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    let scanline = 1.0 - params.x * (0.5 + 0.5 * sin(uv.y * global.resolution.y * 3.14159265));
+    return vec4<f32>(select(vec3<f32>(0.0), color * scanline, inside), 1.0);
+}
@@ -0,0 +1,36 @@
+const FXAA_REDUCE_MIN: f32 = 0.0078125;
+const FXAA_REDUCE_MUL: f32 = 0.125;
+const FXAA_SPAN_MAX: f32 = 8.0;
+
+fn sample_rgb(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(input_texture, input_sampler, uv, 0.0).rgb;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / global.resolution;
+    let luma = vec3<f32>(0.299, 0.587, 0.114);
+    let center = textureSample(input_texture, input_sampler, in.uv);
+
+    let luma_nw = dot(sample_rgb(in.uv + vec2<f32>(-1.0, -1.0) * texel), luma);
+    let luma_ne = dot(sample_rgb(in.uv + vec2<f32>(1.0, -1.0) * texel), luma);
+    let luma_sw = dot(sample_rgb(in.uv + vec2<f32>(-1.0, 1.0) * texel), luma);
+    let luma_se = dot(sample_rgb(in.uv + vec2<f32>(1.0, 1.0) * texel), luma);
+    let luma_m = dot(center.rgb, luma);
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    var direction = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+    let inverse_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * inverse_min, vec2<f32>(-FXAA_SPAN_MAX), vec2<f32>(FXAA_SPAN_MAX)) * texel;
+
+    let rgb_a = 0.5 * (sample_rgb(in.uv + direction * (1.0 / 3.0 - 0.5)) + sample_rgb(in.uv + direction * (2.0 / 3.0 - 0.5)));
+    let rgb_b = rgb_a * 0.5 + 0.25 * (sample_rgb(in.uv - direction * 0.5) + sample_rgb(in.uv + direction * 0.5));
+    let luma_b = dot(rgb_b, luma);
+    let outside = luma_b < luma_min || luma_b > luma_max;
+    return vec4<f32>(select(rgb_b, rgb_a, outside), center.a);
+}
@@ -0,0 +1,9 @@
+@group(1) @binding(1)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let luma = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return vec4<f32>(mix(color.rgb, vec3<f32>(luma), params.x), color.a);
+}
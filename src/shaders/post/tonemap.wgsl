@@ -0,0 +1,14 @@
+@group(1) @binding(1)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let exposed = color.rgb * params.x;
+    let mapped = clamp(
+        (exposed * (2.51 * exposed + 0.03)) / (exposed * (2.43 * exposed + 0.59) + 0.14),
+        vec3<f32>(0.0),
+        vec3<f32>(1.0),
+    );
+    return vec4<f32>(pow(mapped, vec3<f32>(1.0 / params.y)), color.a);
+}
@@ -0,0 +1,10 @@
+@group(1) @binding(1)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let distance = length(in.uv - vec2<f32>(0.5));
+    let vignette = smoothstep(params.y, params.y - params.z, distance);
+    return vec4<f32>(color.rgb * mix(1.0, vignette, params.x), color.a);
+}
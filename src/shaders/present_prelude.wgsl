@@ -0,0 +1,35 @@
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> @builtin(position) vec4<f32> {
+    let x = f32(1 - i32(in_vertex_index)) * 10.;
+    let y = f32(i32(in_vertex_index & 1u) * 2 - 1) * 10.;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@group(0) @binding(0)
+var tris_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var tris_sampler: sampler;
+
+struct Global {
+    framebuffer_size: vec2<f32>,
+    surface_size: vec2<f32>,
+    viewport_offset: vec2<f32>,
+    viewport_size: vec2<f32>,
+    bar_color: vec4<f32>,
+    mouse_position: vec2<f32>,
+    time: f32,
+    frame: u32,
+};
+
+@group(1) @binding(0) // 1.
+var<uniform> global: Global;
+
+fn framebuffer_uv(position: vec2<f32>) -> vec2<f32> {
+    return (position - global.viewport_offset) / global.viewport_size;
+}
+
+fn inside_viewport(uv: vec2<f32>) -> bool {
+    return all(uv >= vec2<f32>(0.0)) && all(uv < vec2<f32>(1.0));
+}
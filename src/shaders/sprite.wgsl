@@ -0,0 +1,37 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var sprite_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var sprite_sampler: sampler;
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = camera.view_projection * vec4<f32>(in.position, 1.0);
+    out.uv = in.uv;
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(sprite_texture, sprite_sampler, in.uv) * in.color;
+}
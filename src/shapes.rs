@@ -0,0 +1,756 @@
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::ops::Range;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::camera::{
+    create_camera_bind_group, create_camera_bind_group_layout, set_viewport, Camera2D,
+};
+use crate::depth::StencilMode;
+use crate::math::TransformStack;
+use crate::texture::RenderableTexture;
+use crate::{color_to_array, ArcedRenderDevice};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShapeVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuShapeVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+type PipelineKey = (
+    wgpu::TextureFormat,
+    Option<wgpu::TextureFormat>,
+    StencilMode,
+    u32,
+);
+
+struct ShapeDraw {
+    indices: Range<u32>,
+    stencil: StencilMode,
+    stencil_reference: u32,
+}
+
+pub struct ShapeRenderer {
+    device: ArcedRenderDevice,
+    shader: wgpu::ShaderModule,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    camera: Option<Camera2D>,
+    transforms: TransformStack,
+    vertices: Vec<ShapeVertex>,
+    depths: Vec<f32>,
+    indices: Vec<u32>,
+    draws: Vec<ShapeDraw>,
+    depth: f32,
+    stencil: StencilMode,
+    stencil_reference: u32,
+}
+
+impl ShapeVertex {
+    pub fn new(x: f32, y: f32, color: wgpu::Color) -> ShapeVertex {
+        ShapeVertex {
+            position: [x, y],
+            color: color_to_array(color),
+        }
+    }
+}
+
+impl GpuShapeVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GpuShapeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl ShapeRenderer {
+    pub fn new(device: &ArcedRenderDevice) -> ShapeRenderer {
+        let shader = device
+            .device
+            .create_shader_module(wgpu::include_wgsl!("shaders/shape.wgsl"));
+        let camera_bind_group_layout = create_camera_bind_group_layout(device);
+        let pipeline_layout =
+            device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Shape Pipeline Layout"),
+                    bind_group_layouts: &[&camera_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        ShapeRenderer {
+            device: device.clone(),
+            shader,
+            camera_bind_group_layout,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            camera: None,
+            transforms: TransformStack::new(),
+            vertices: Vec::new(),
+            depths: Vec::new(),
+            indices: Vec::new(),
+            draws: Vec::new(),
+            depth: 0.0,
+            stencil: StencilMode::Disabled,
+            stencil_reference: 0,
+        }
+    }
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.depths.clear();
+        self.indices.clear();
+        self.draws.clear();
+    }
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+    pub fn set_camera(&mut self, camera: Option<Camera2D>) {
+        self.camera = camera;
+    }
+    pub const fn camera(&self) -> Option<&Camera2D> {
+        self.camera.as_ref()
+    }
+    pub fn transform_stack(&mut self) -> &mut TransformStack {
+        &mut self.transforms
+    }
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth;
+    }
+    pub fn set_stencil(&mut self, stencil: StencilMode, reference: u32) {
+        self.stencil = stencil;
+        self.stencil_reference = reference;
+    }
+    pub fn push(&mut self, vertices: &[ShapeVertex], indices: &[u32]) {
+        let base = self.vertices.len() as u32;
+        let transform = self.transforms.current();
+        self.vertices
+            .extend(vertices.iter().map(|vertex| ShapeVertex {
+                position: transform.transform_point(vertex.position),
+                color: vertex.color,
+            }));
+        self.depths
+            .extend(std::iter::repeat(self.depth).take(vertices.len()));
+        let start = self.indices.len() as u32;
+        self.indices
+            .extend(indices.iter().map(|index| base + index));
+        let end = self.indices.len() as u32;
+
+        match self.draws.last_mut() {
+            Some(draw)
+                if draw.stencil == self.stencil
+                    && draw.stencil_reference == self.stencil_reference =>
+            {
+                draw.indices.end = end
+            }
+            _ => self.draws.push(ShapeDraw {
+                indices: start..end,
+                stencil: self.stencil,
+                stencil_reference: self.stencil_reference,
+            }),
+        }
+    }
+    pub fn fill_triangle(&mut self, a: [f32; 2], b: [f32; 2], c: [f32; 2], color: wgpu::Color) {
+        let color = color_to_array(color);
+        self.push(
+            &[
+                ShapeVertex { position: a, color },
+                ShapeVertex { position: b, color },
+                ShapeVertex { position: c, color },
+            ],
+            &[0, 1, 2],
+        );
+    }
+    pub fn fill_triangle_colored(&mut self, vertices: [ShapeVertex; 3]) {
+        self.push(&vertices, &[0, 1, 2]);
+    }
+    pub fn stroke_triangle(
+        &mut self,
+        a: [f32; 2],
+        b: [f32; 2],
+        c: [f32; 2],
+        thickness: f32,
+        color: wgpu::Color,
+    ) {
+        self.stroke_path(&[a, b, c], true, thickness, color);
+    }
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: wgpu::Color) {
+        self.fill_rect_gradient(x, y, width, height, [color; 4]);
+    }
+    pub fn fill_rect_gradient(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        colors: [wgpu::Color; 4],
+    ) {
+        self.push(
+            &[
+                ShapeVertex::new(x, y, colors[0]),
+                ShapeVertex::new(x + width, y, colors[1]),
+                ShapeVertex::new(x + width, y + height, colors[2]),
+                ShapeVertex::new(x, y + height, colors[3]),
+            ],
+            &[0, 1, 2, 0, 2, 3],
+        );
+    }
+    pub fn stroke_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        thickness: f32,
+        color: wgpu::Color,
+    ) {
+        self.stroke_path(
+            &[
+                [x, y],
+                [x + width, y],
+                [x + width, y + height],
+                [x, y + height],
+            ],
+            true,
+            thickness,
+            color,
+        );
+    }
+    pub fn fill_rounded_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        color: wgpu::Color,
+    ) {
+        let points = rounded_rect_points(x, y, width, height, radius);
+        self.fill_fan([x + width / 2.0, y + height / 2.0], &points, color);
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub fn stroke_rounded_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        thickness: f32,
+        color: wgpu::Color,
+    ) {
+        let points = rounded_rect_points(x, y, width, height, radius);
+        self.stroke_path(&points, true, thickness, color);
+    }
+    pub fn fill_circle(&mut self, x: f32, y: f32, radius: f32, color: wgpu::Color) {
+        self.fill_ellipse(x, y, radius, radius, color);
+    }
+    pub fn stroke_circle(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        thickness: f32,
+        color: wgpu::Color,
+    ) {
+        self.stroke_ellipse(x, y, radius, radius, thickness, color);
+    }
+    pub fn fill_ellipse(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        color: wgpu::Color,
+    ) {
+        let points = ellipse_points(x, y, radius_x, radius_y);
+        self.fill_fan([x, y], &points, color);
+    }
+    pub fn stroke_ellipse(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        thickness: f32,
+        color: wgpu::Color,
+    ) {
+        let points = ellipse_points(x, y, radius_x, radius_y);
+        self.stroke_path(&points, true, thickness, color);
+    }
+    pub fn line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, thickness: f32, color: wgpu::Color) {
+        self.stroke_path(&[[x0, y0], [x1, y1]], false, thickness, color);
+    }
+    pub fn polyline(&mut self, points: &[[f32; 2]], thickness: f32, color: wgpu::Color) {
+        self.stroke_path(points, false, thickness, color);
+    }
+    pub fn fill_polygon(&mut self, points: &[[f32; 2]], color: wgpu::Color) {
+        let color = color_to_array(color);
+        let vertices = points
+            .iter()
+            .map(|&position| ShapeVertex { position, color })
+            .collect::<Vec<_>>();
+        self.push(&vertices, &triangulate(points));
+    }
+    pub fn stroke_polygon(&mut self, points: &[[f32; 2]], thickness: f32, color: wgpu::Color) {
+        self.stroke_path(points, true, thickness, color);
+    }
+    pub fn stroke_path(
+        &mut self,
+        points: &[[f32; 2]],
+        closed: bool,
+        thickness: f32,
+        color: wgpu::Color,
+    ) {
+        let (vertices, indices) = stroke_geometry(points, closed, thickness, color_to_array(color));
+        if !indices.is_empty() {
+            self.push(&vertices, &indices);
+        }
+    }
+    pub fn flush(&mut self, target: &RenderableTexture) {
+        let mut encoder =
+            self.device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Shape Command Encoder"),
+                });
+        self.flush_with_encoder(&mut encoder, target);
+        self.device.queue.submit([encoder.finish()]);
+    }
+    pub fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let camera = self
+            .camera
+            .unwrap_or_else(|| Camera2D::new(target.width() as f32, target.height() as f32));
+        let Some(viewport) = camera.clipped_viewport(target.width(), target.height()) else {
+            self.clear();
+            return;
+        };
+        let camera_bind_group = create_camera_bind_group(
+            &self.device,
+            &self.camera_bind_group_layout,
+            &camera,
+            viewport,
+        );
+        let vertices = self
+            .vertices
+            .iter()
+            .zip(&self.depths)
+            .map(|(vertex, &depth)| GpuShapeVertex {
+                position: [vertex.position[0], vertex.position[1], depth],
+                color: vertex.color,
+            })
+            .collect::<Vec<_>>();
+        let vertex_buffer = self
+            .device
+            .device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Shape Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = self
+            .device
+            .device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Shape Index Buffer"),
+                contents: bytemuck::cast_slice(&self.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        let view = target.create_view();
+        let multisampled_view = target.create_multisampled_view();
+        let depth_view = target.create_depth_view();
+        let format = target.format();
+        let depth_format = target.depth_format();
+        let sample_count = target.sample_count();
+        for index in 0..self.draws.len() {
+            let stencil = self.draws[index].stencil;
+            self.ensure_pipeline((format, depth_format, stencil, sample_count));
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shape Render Pass"),
+                color_attachments: &[Some(target.color_attachment(
+                    &view,
+                    multisampled_view.as_ref(),
+                    wgpu::LoadOp::Load,
+                ))],
+                depth_stencil_attachment: target.depth_attachment(depth_view.as_ref()),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            set_viewport(&mut render_pass, viewport);
+            render_pass.set_bind_group(0, &camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            for draw in &self.draws {
+                let key = (format, depth_format, draw.stencil, sample_count);
+                render_pass.set_pipeline(&self.pipelines[&key]);
+                render_pass.set_stencil_reference(draw.stencil_reference);
+                render_pass.draw_indexed(draw.indices.clone(), 0, 0..1);
+            }
+        }
+
+        self.clear();
+    }
+    fn fill_fan(&mut self, center: [f32; 2], points: &[[f32; 2]], color: wgpu::Color) {
+        let color = color_to_array(color);
+        let mut vertices = Vec::with_capacity(points.len() + 1);
+        vertices.push(ShapeVertex {
+            position: center,
+            color,
+        });
+        vertices.extend(
+            points
+                .iter()
+                .map(|&position| ShapeVertex { position, color }),
+        );
+
+        let count = points.len() as u32;
+        let mut indices = Vec::with_capacity(points.len() * 3);
+        for i in 0..count {
+            indices.extend_from_slice(&[0, i + 1, (i + 1) % count + 1]);
+        }
+
+        self.push(&vertices, &indices);
+    }
+    fn ensure_pipeline(&mut self, key: PipelineKey) {
+        if self.pipelines.contains_key(&key) {
+            return;
+        }
+        let (format, depth_format, stencil, sample_count) = key;
+        let pipeline = self
+            .device
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Shape Render Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader,
+                    entry_point: "vs_main",
+                    buffers: &[GpuShapeVertex::layout()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: stencil.color_writes(),
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: stencil.depth_stencil_state(depth_format),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+        self.pipelines.insert(key, pipeline);
+    }
+}
+
+fn stroke_geometry(
+    points: &[[f32; 2]],
+    closed: bool,
+    thickness: f32,
+    color: [f32; 4],
+) -> (Vec<ShapeVertex>, Vec<u32>) {
+    let count = points.len();
+    if count < 2 {
+        return (Vec::new(), Vec::new());
+    }
+    let half = thickness / 2.0;
+    let mut vertices = Vec::with_capacity(count * 2);
+
+    for i in 0..count {
+        let previous = if i > 0 {
+            Some(points[i - 1])
+        } else if closed {
+            Some(points[count - 1])
+        } else {
+            None
+        };
+        let next = if i + 1 < count {
+            Some(points[i + 1])
+        } else if closed {
+            Some(points[0])
+        } else {
+            None
+        };
+        let point = points[i];
+        let incoming = previous.map(|previous| segment_normal(previous, point));
+        let outgoing = next.map(|next| segment_normal(point, next));
+
+        let offset = match (incoming, outgoing) {
+            (Some(a), Some(b)) => {
+                let miter = normalize([a[0] + b[0], a[1] + b[1]]);
+                let cosine = (miter[0] * b[0] + miter[1] * b[1]).max(0.25);
+                let length = half / cosine;
+                [miter[0] * length, miter[1] * length]
+            }
+            (Some(normal), None) | (None, Some(normal)) => [normal[0] * half, normal[1] * half],
+            (None, None) => [0.0, 0.0],
+        };
+
+        vertices.push(ShapeVertex {
+            position: [point[0] + offset[0], point[1] + offset[1]],
+            color,
+        });
+        vertices.push(ShapeVertex {
+            position: [point[0] - offset[0], point[1] - offset[1]],
+            color,
+        });
+    }
+
+    let segments = if closed { count } else { count - 1 };
+    let mut indices = Vec::with_capacity(segments * 6);
+    for i in 0..segments {
+        let a = (i * 2) as u32;
+        let b = (((i + 1) % count) * 2) as u32;
+        indices.extend_from_slice(&[a, a + 1, b + 1, a, b + 1, b]);
+    }
+
+    (vertices, indices)
+}
+
+fn segment_normal(from: [f32; 2], to: [f32; 2]) -> [f32; 2] {
+    let direction = normalize([to[0] - from[0], to[1] - from[1]]);
+    [-direction[1], direction[0]]
+}
+
+fn normalize(vector: [f32; 2]) -> [f32; 2] {
+    let length = (vector[0] * vector[0] + vector[1] * vector[1]).sqrt();
+    if length == 0.0 {
+        [0.0, 0.0]
+    } else {
+        [vector[0] / length, vector[1] / length]
+    }
+}
+
+fn segment_count(radius: f32) -> usize {
+    ((radius.abs().sqrt() * 6.0) as usize).clamp(12, 256)
+}
+
+fn ellipse_points(x: f32, y: f32, radius_x: f32, radius_y: f32) -> Vec<[f32; 2]> {
+    let segments = segment_count(radius_x.max(radius_y));
+    (0..segments)
+        .map(|i| {
+            let angle = i as f32 / segments as f32 * TAU;
+            [x + angle.cos() * radius_x, y + angle.sin() * radius_y]
+        })
+        .collect()
+}
+
+fn rounded_rect_points(x: f32, y: f32, width: f32, height: f32, radius: f32) -> Vec<[f32; 2]> {
+    let radius = radius.clamp(0.0, width.min(height) / 2.0);
+    if radius == 0.0 {
+        return vec![
+            [x, y],
+            [x + width, y],
+            [x + width, y + height],
+            [x, y + height],
+        ];
+    }
+    let segments = (segment_count(radius) / 4).max(3);
+    let corners = [
+        ([x + width - radius, y + radius], -0.25),
+        ([x + width - radius, y + height - radius], 0.0),
+        ([x + radius, y + height - radius], 0.25),
+        ([x + radius, y + radius], 0.5),
+    ];
+    let mut points = Vec::with_capacity(segments * 4 + 4);
+    for (center, start) in corners {
+        for i in 0..=segments {
+            let angle = (start + i as f32 / segments as f32 * 0.25) * TAU;
+            points.push([
+                center[0] + angle.cos() * radius,
+                center[1] + angle.sin() * radius,
+            ]);
+        }
+    }
+    points
+}
+
+fn triangulate(points: &[[f32; 2]]) -> Vec<u32> {
+    let count = points.len();
+    if count < 3 {
+        return Vec::new();
+    }
+
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (points[a], points[b], points[c]);
+        (b[0] - a[0]) * (c[1] - b[1]) - (b[1] - a[1]) * (c[0] - b[0])
+    };
+    let area: f32 = (0..count)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % count]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+
+    let mut remaining = (0..count).collect::<Vec<_>>();
+    if area < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut indices = Vec::with_capacity((count - 2) * 3);
+    while remaining.len() > 3 {
+        let length = remaining.len();
+        let ear = (0..length).find(|&i| {
+            let a = remaining[(i + length - 1) % length];
+            let b = remaining[i];
+            let c = remaining[(i + 1) % length];
+            cross(a, b, c) > 0.0
+                && !remaining
+                    .iter()
+                    .filter(|&&p| p != a && p != b && p != c)
+                    .any(|&p| {
+                        cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+                    })
+        });
+        let Some(i) = ear else {
+            break;
+        };
+        indices.extend_from_slice(&[
+            remaining[(i + length - 1) % length] as u32,
+            remaining[i] as u32,
+            remaining[(i + 1) % length] as u32,
+        ]);
+        remaining.remove(i);
+    }
+    if remaining.len() == 3 {
+        indices.extend(remaining.iter().map(|&i| i as u32));
+    }
+
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangulated_area(points: &[[f32; 2]], indices: &[u32]) -> f32 {
+        indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| points[triangle[i] as usize]);
+                ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn triangulate_square() {
+        let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let indices = triangulate(&square);
+        assert_eq!(indices.len(), 6);
+        assert_eq!(triangulated_area(&square, &indices), 1.0);
+    }
+
+    #[test]
+    fn triangulate_clockwise_polygon() {
+        let square = [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]];
+        let indices = triangulate(&square);
+        assert_eq!(indices.len(), 6);
+        assert_eq!(triangulated_area(&square, &indices), 1.0);
+    }
+
+    #[test]
+    fn triangulate_concave_polygon() {
+        let shape = [
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ];
+        let indices = triangulate(&shape);
+        assert_eq!(indices.len(), 12);
+        assert_eq!(triangulated_area(&shape, &indices), 3.0);
+    }
+
+    #[test]
+    fn triangulate_degenerate_input() {
+        assert!(triangulate(&[]).is_empty());
+        assert!(triangulate(&[[0.0, 0.0], [1.0, 1.0]]).is_empty());
+    }
+
+    #[test]
+    fn stroke_open_path() {
+        let (vertices, indices) = stroke_geometry(&[[0.0, 0.0], [10.0, 0.0]], false, 2.0, [1.0; 4]);
+        let positions = vertices
+            .iter()
+            .map(|vertex| vertex.position)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [[0.0, 1.0], [0.0, -1.0], [10.0, 1.0], [10.0, -1.0]]
+        );
+        assert_eq!(indices, [0, 1, 3, 0, 3, 2]);
+    }
+
+    #[test]
+    fn stroke_closed_path() {
+        let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let (vertices, indices) = stroke_geometry(&square, true, 0.5, [1.0; 4]);
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(indices.len(), 24);
+        assert!(indices
+            .iter()
+            .all(|&index| (index as usize) < vertices.len()));
+    }
+
+    #[test]
+    fn stroke_miter_keeps_thickness() {
+        let (vertices, _) = stroke_geometry(
+            &[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]],
+            false,
+            2.0,
+            [1.0; 4],
+        );
+        let [x, y] = vertices[2].position;
+        assert!((x - 9.0).abs() < 1e-5 && (y - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn stroke_needs_two_points() {
+        let (vertices, indices) = stroke_geometry(&[[0.0, 0.0]], false, 1.0, [1.0; 4]);
+        assert!(vertices.is_empty() && indices.is_empty());
+    }
+}
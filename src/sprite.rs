@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::camera::{
+    create_camera_bind_group, create_camera_bind_group_layout, set_viewport, Camera2D,
+};
+use crate::depth::StencilMode;
+use crate::math::{Rect, TransformStack};
+use crate::texture::{
+    create_texture_bind_group, create_texture_bind_group_layout, RenderableTexture, Texture,
+    TextureSampler,
+};
+use crate::{color_to_array, ArcedRenderDevice};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteVertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub origin: [f32; 2],
+    pub tint: wgpu::Color,
+    pub source: Option<Rect>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub depth: f32,
+}
+
+struct SpriteDraw {
+    texture: Texture,
+    indices: Range<u32>,
+    stencil: StencilMode,
+    stencil_reference: u32,
+}
+
+type PipelineKey = (
+    wgpu::TextureFormat,
+    bool,
+    Option<wgpu::TextureFormat>,
+    StencilMode,
+    u32,
+);
+
+pub struct SpriteBatch {
+    device: ArcedRenderDevice,
+    shader: wgpu::ShaderModule,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    sampler: TextureSampler,
+    camera: Option<Camera2D>,
+    transforms: TransformStack,
+    vertices: Vec<SpriteVertex>,
+    indices: Vec<u32>,
+    draws: Vec<SpriteDraw>,
+    stencil: StencilMode,
+    stencil_reference: u32,
+}
+
+impl SpriteVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x4];
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl Sprite {
+    pub fn new(x: f32, y: f32) -> Sprite {
+        Sprite {
+            position: [x, y],
+            ..Default::default()
+        }
+    }
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.scale = [x, y];
+        self
+    }
+    pub fn origin(mut self, x: f32, y: f32) -> Self {
+        self.origin = [x, y];
+        self
+    }
+    pub fn tint(mut self, tint: wgpu::Color) -> Self {
+        self.tint = tint;
+        self
+    }
+    pub fn source(mut self, source: Rect) -> Self {
+        self.source = Some(source);
+        self
+    }
+    pub fn flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+    pub fn depth(mut self, depth: f32) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Sprite {
+            position: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            origin: [0.0, 0.0],
+            tint: wgpu::Color::WHITE,
+            source: None,
+            flip_x: false,
+            flip_y: false,
+            depth: 0.0,
+        }
+    }
+}
+
+impl SpriteBatch {
+    pub fn new(device: &ArcedRenderDevice) -> SpriteBatch {
+        let shader = device
+            .device
+            .create_shader_module(wgpu::include_wgsl!("shaders/sprite.wgsl"));
+        let texture_bind_group_layout = create_texture_bind_group_layout(device);
+        let camera_bind_group_layout = create_camera_bind_group_layout(device);
+        let pipeline_layout =
+            device
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Sprite Pipeline Layout"),
+                    bind_group_layouts: &[&texture_bind_group_layout, &camera_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        SpriteBatch {
+            device: device.clone(),
+            shader,
+            texture_bind_group_layout,
+            camera_bind_group_layout,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            sampler: TextureSampler::new(device),
+            camera: None,
+            transforms: TransformStack::new(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            draws: Vec::new(),
+            stencil: StencilMode::Disabled,
+            stencil_reference: 0,
+        }
+    }
+    pub fn set_sampler(&mut self, sampler: TextureSampler) {
+        self.sampler = sampler;
+    }
+    pub fn set_stencil(&mut self, stencil: StencilMode, reference: u32) {
+        self.stencil = stencil;
+        self.stencil_reference = reference;
+    }
+    pub fn set_camera(&mut self, camera: Option<Camera2D>) {
+        self.camera = camera;
+    }
+    pub const fn camera(&self) -> Option<&Camera2D> {
+        self.camera.as_ref()
+    }
+    pub fn transform_stack(&mut self) -> &mut TransformStack {
+        &mut self.transforms
+    }
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.draws.clear();
+    }
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.indices.len() / 6
+    }
+    pub fn draw(&mut self, texture: &Texture, sprite: Sprite) {
+        let source = sprite.source.unwrap_or(Rect::new(
+            0.0,
+            0.0,
+            texture.width() as f32,
+            texture.height() as f32,
+        ));
+
+        let transform = self.transforms.current();
+        let (sin, cos) = sprite.rotation.sin_cos();
+        let corners = [
+            [0.0, 0.0],
+            [source.width, 0.0],
+            [source.width, source.height],
+            [0.0, source.height],
+        ];
+        let positions = corners.map(|corner| {
+            let x = (corner[0] - sprite.origin[0]) * sprite.scale[0];
+            let y = (corner[1] - sprite.origin[1]) * sprite.scale[1];
+            let [x, y] = transform.transform_point([
+                sprite.position[0] + x * cos - y * sin,
+                sprite.position[1] + x * sin + y * cos,
+            ]);
+            [x, y, sprite.depth]
+        });
+
+        let (texture_width, texture_height) = (texture.width() as f32, texture.height() as f32);
+        let (mut left, mut right) = (source.x / texture_width, source.right() / texture_width);
+        let (mut top, mut bottom) = (source.y / texture_height, source.bottom() / texture_height);
+        if sprite.flip_x {
+            std::mem::swap(&mut left, &mut right);
+        }
+        if sprite.flip_y {
+            std::mem::swap(&mut top, &mut bottom);
+        }
+        let uvs = [[left, top], [right, top], [right, bottom], [left, bottom]];
+
+        let mut color = color_to_array(sprite.tint);
+        if texture.is_premultiplied() {
+            color = [
+                color[0] * color[3],
+                color[1] * color[3],
+                color[2] * color[3],
+                color[3],
+            ];
+        }
+        let base = self.vertices.len() as u32;
+        for (position, uv) in positions.into_iter().zip(uvs) {
+            self.vertices.push(SpriteVertex {
+                position,
+                uv,
+                color,
+            });
+        }
+        let start = self.indices.len() as u32;
+        self.indices
+            .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        let end = self.indices.len() as u32;
+
+        match self.draws.last_mut() {
+            Some(draw)
+                if draw.texture.ptr_eq(texture)
+                    && draw.stencil == self.stencil
+                    && draw.stencil_reference == self.stencil_reference =>
+            {
+                draw.indices.end = end
+            }
+            _ => self.draws.push(SpriteDraw {
+                texture: texture.clone(),
+                indices: start..end,
+                stencil: self.stencil,
+                stencil_reference: self.stencil_reference,
+            }),
+        }
+    }
+    pub fn flush(&mut self, target: &RenderableTexture) {
+        let mut encoder =
+            self.device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Sprite Command Encoder"),
+                });
+        self.flush_with_encoder(&mut encoder, target);
+        self.device.queue.submit([encoder.finish()]);
+    }
+    pub fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let camera = self
+            .camera
+            .unwrap_or_else(|| Camera2D::new(target.width() as f32, target.height() as f32));
+        let Some(viewport) = camera.clipped_viewport(target.width(), target.height()) else {
+            self.clear();
+            return;
+        };
+        let camera_bind_group = create_camera_bind_group(
+            &self.device,
+            &self.camera_bind_group_layout,
+            &camera,
+            viewport,
+        );
+        let vertex_buffer = self
+            .device
+            .device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Sprite Vertex Buffer"),
+                contents: bytemuck::cast_slice(&self.vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = self
+            .device
+            .device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Sprite Index Buffer"),
+                contents: bytemuck::cast_slice(&self.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        let bind_groups = self
+            .draws
+            .iter()
+            .map(|draw| {
+                create_texture_bind_group(
+                    &self.device,
+                    &self.texture_bind_group_layout,
+                    draw.texture.view(),
+                    &self.sampler,
+                )
+            })
+            .collect::<Vec<_>>();
+        let view = target.create_view();
+        let multisampled_view = target.create_multisampled_view();
+        let depth_view = target.create_depth_view();
+        let format = target.format();
+        let depth_format = target.depth_format();
+        let sample_count = target.sample_count();
+        for index in 0..self.draws.len() {
+            let draw = &self.draws[index];
+            self.ensure_pipeline((
+                format,
+                draw.texture.is_premultiplied(),
+                depth_format,
+                draw.stencil,
+                sample_count,
+            ));
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Sprite Render Pass"),
+                color_attachments: &[Some(target.color_attachment(
+                    &view,
+                    multisampled_view.as_ref(),
+                    wgpu::LoadOp::Load,
+                ))],
+                depth_stencil_attachment: target.depth_attachment(depth_view.as_ref()),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            set_viewport(&mut render_pass, viewport);
+            render_pass.set_bind_group(1, &camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            for (draw, bind_group) in self.draws.iter().zip(&bind_groups) {
+                let premultiplied = draw.texture.is_premultiplied();
+                let key = (
+                    format,
+                    premultiplied,
+                    depth_format,
+                    draw.stencil,
+                    sample_count,
+                );
+                render_pass.set_pipeline(&self.pipelines[&key]);
+                render_pass.set_stencil_reference(draw.stencil_reference);
+                render_pass.set_bind_group(0, bind_group, &[]);
+                render_pass.draw_indexed(draw.indices.clone(), 0, 0..1);
+            }
+        }
+
+        self.clear();
+    }
+    fn ensure_pipeline(&mut self, key: PipelineKey) {
+        if self.pipelines.contains_key(&key) {
+            return;
+        }
+        let (format, premultiplied, depth_format, stencil, sample_count) = key;
+        let blend = if premultiplied {
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING
+        } else {
+            wgpu::BlendState::ALPHA_BLENDING
+        };
+        let pipeline = self
+            .device
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Sprite Render Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader,
+                    entry_point: "vs_main",
+                    buffers: &[SpriteVertex::layout()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(blend),
+                        write_mask: stencil.color_writes(),
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: stencil.depth_stencil_state(depth_format),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+        self.pipelines.insert(key, pipeline);
+    }
+}
@@ -0,0 +1,100 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Vsync {
+    #[default]
+    On,
+    Off,
+    Adaptive,
+}
+
+#[derive(Debug, Clone)]
+pub struct SurfaceOptions {
+    pub vsync: Vsync,
+    pub preferred_formats: Vec<wgpu::TextureFormat>,
+    pub srgb: bool,
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    pub frame_latency: u32,
+}
+
+impl Vsync {
+    pub fn present_modes(self) -> &'static [wgpu::PresentMode] {
+        match self {
+            Vsync::On => &[wgpu::PresentMode::Fifo],
+            Vsync::Off => &[
+                wgpu::PresentMode::Immediate,
+                wgpu::PresentMode::Mailbox,
+                wgpu::PresentMode::Fifo,
+            ],
+            Vsync::Adaptive => &[wgpu::PresentMode::FifoRelaxed, wgpu::PresentMode::Fifo],
+        }
+    }
+    pub(crate) fn select(self, capabilities: &wgpu::SurfaceCapabilities) -> wgpu::PresentMode {
+        self.present_modes()
+            .iter()
+            .find(|mode| capabilities.present_modes.contains(mode))
+            .copied()
+            .unwrap_or(wgpu::PresentMode::Fifo)
+    }
+}
+
+impl SurfaceOptions {
+    pub fn vsync(mut self, vsync: Vsync) -> Self {
+        self.vsync = vsync;
+        self
+    }
+    pub fn preferred_formats(mut self, formats: &[wgpu::TextureFormat]) -> Self {
+        self.preferred_formats = formats.to_vec();
+        self
+    }
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+    pub fn alpha_mode(mut self, alpha_mode: wgpu::CompositeAlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+    pub fn frame_latency(mut self, frame_latency: u32) -> Self {
+        self.frame_latency = frame_latency;
+        self
+    }
+    pub(crate) fn select_format(
+        &self,
+        capabilities: &wgpu::SurfaceCapabilities,
+    ) -> Option<wgpu::TextureFormat> {
+        self.preferred_formats
+            .iter()
+            .find(|format| capabilities.formats.contains(format))
+            .or_else(|| {
+                capabilities
+                    .formats
+                    .iter()
+                    .find(|format| format.is_srgb() == self.srgb)
+            })
+            .or_else(|| capabilities.formats.first())
+            .copied()
+    }
+    pub(crate) fn select_alpha_mode(
+        &self,
+        capabilities: &wgpu::SurfaceCapabilities,
+    ) -> wgpu::CompositeAlphaMode {
+        if self.alpha_mode == wgpu::CompositeAlphaMode::Auto
+            || capabilities.alpha_modes.contains(&self.alpha_mode)
+        {
+            self.alpha_mode
+        } else {
+            capabilities.alpha_modes[0]
+        }
+    }
+}
+
+impl Default for SurfaceOptions {
+    fn default() -> Self {
+        SurfaceOptions {
+            vsync: Vsync::On,
+            preferred_formats: vec![],
+            srgb: true,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            frame_latency: 2,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::texture::{RenderableTexture, Texture};
+use crate::{ArcedRenderDevice, Result};
+
+pub struct RenderTarget {
+    device: ArcedRenderDevice,
+    texture: RenderableTexture,
+    usage: wgpu::TextureUsages,
+}
+
+impl RenderTarget {
+    pub fn new(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+    ) -> RenderTarget {
+        RenderTarget {
+            device: device.clone(),
+            texture: RenderableTexture::uninitialized(device, width, height, format, 1, usage),
+            usage,
+        }
+    }
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        if width == 0 || height == 0 || (width, height) == (self.width(), self.height()) {
+            return Ok(());
+        }
+        self.texture = self.create_texture(&self.device, width, height)?;
+        Ok(())
+    }
+    pub fn recreate(&self, device: &ArcedRenderDevice) -> Result<RenderTarget> {
+        Ok(RenderTarget {
+            device: device.clone(),
+            texture: self.create_texture(device, self.width(), self.height())?,
+            usage: self.usage,
+        })
+    }
+    pub const fn usage(&self) -> wgpu::TextureUsages {
+        self.usage
+    }
+    pub fn texture(&self) -> &Texture {
+        self.texture.texture()
+    }
+    pub const fn renderable_texture(&self) -> &RenderableTexture {
+        &self.texture
+    }
+    pub fn renderable_texture_mut(&mut self) -> &mut RenderableTexture {
+        &mut self.texture
+    }
+    fn create_texture(
+        &self,
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderableTexture> {
+        let mut texture =
+            RenderableTexture::uninitialized(device, width, height, self.format(), 1, self.usage);
+        texture.set_depth_format(self.depth_format());
+        texture.set_sample_count(self.sample_count())?;
+        Ok(texture)
+    }
+}
+
+impl Deref for RenderTarget {
+    type Target = RenderableTexture;
+
+    fn deref(&self) -> &RenderableTexture {
+        &self.texture
+    }
+}
+
+impl DerefMut for RenderTarget {
+    fn deref_mut(&mut self) -> &mut RenderableTexture {
+        &mut self.texture
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use crate::math::Rect;
+use crate::texture::{Texture, TextureOptions};
+use crate::ArcedRenderDevice;
+
+use super::font::Font;
+use super::FontId;
+
+const INITIAL_SIZE: u32 = 256;
+const PADDING: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    character: char,
+    size: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CachedGlyph {
+    pub source: Rect,
+    pub offset: [f32; 2],
+}
+
+pub(crate) struct GlyphCache {
+    device: ArcedRenderDevice,
+    texture: Texture,
+    size: u32,
+    cursor: (u32, u32),
+    shelf_height: u32,
+    glyphs: HashMap<GlyphKey, Option<CachedGlyph>>,
+}
+
+impl GlyphCache {
+    pub fn new(device: &ArcedRenderDevice) -> GlyphCache {
+        GlyphCache {
+            device: device.clone(),
+            texture: create_atlas_texture(device, INITIAL_SIZE),
+            size: INITIAL_SIZE,
+            cursor: (PADDING, PADDING),
+            shelf_height: 0,
+            glyphs: HashMap::new(),
+        }
+    }
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    pub fn glyph(
+        &mut self,
+        font_id: FontId,
+        font: &Font,
+        character: char,
+        size: f32,
+    ) -> Option<CachedGlyph> {
+        let key = GlyphKey {
+            font: font_id,
+            character,
+            size: font.raster_size(size),
+        };
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return *glyph;
+        }
+
+        let rasterized = match font.rasterize(character, size) {
+            Some(rasterized) => rasterized,
+            None => {
+                self.glyphs.insert(key, None);
+                return None;
+            }
+        };
+        let (x, y) = self.allocate(rasterized.width, rasterized.height)?;
+        let pixels = rasterized
+            .coverage
+            .iter()
+            .flat_map(|&coverage| [255, 255, 255, coverage])
+            .collect::<Vec<u8>>();
+        self.device.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: self.texture.wgpu_texture(),
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(rasterized.width * 4),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: rasterized.width,
+                height: rasterized.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        let glyph = CachedGlyph {
+            source: Rect::new(
+                x as f32,
+                y as f32,
+                rasterized.width as f32,
+                rasterized.height as f32,
+            ),
+            offset: rasterized.offset,
+        };
+        self.glyphs.insert(key, Some(glyph));
+        Some(glyph)
+    }
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if let Some(position) = self.try_allocate(width, height) {
+            return Some(position);
+        }
+
+        let max_size = self.device.device.limits().max_texture_dimension_2d;
+        if self.size >= max_size {
+            return None;
+        }
+        self.size = (self.size * 2).min(max_size);
+        self.texture = create_atlas_texture(&self.device, self.size);
+        self.cursor = (PADDING, PADDING);
+        self.shelf_height = 0;
+        self.glyphs.clear();
+        self.try_allocate(width, height)
+    }
+    fn try_allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if self.cursor.0 + width + PADDING > self.size {
+            self.cursor = (PADDING, self.cursor.1 + self.shelf_height + PADDING);
+            self.shelf_height = 0;
+        }
+        if self.cursor.0 + width + PADDING > self.size
+            || self.cursor.1 + height + PADDING > self.size
+        {
+            return None;
+        }
+
+        let position = self.cursor;
+        self.cursor.0 += width + PADDING;
+        self.shelf_height = self.shelf_height.max(height);
+        Some(position)
+    }
+}
+
+fn create_atlas_texture(device: &ArcedRenderDevice, size: u32) -> Texture {
+    Texture::create_rgba8(
+        device,
+        size,
+        size,
+        &vec![0u8; (size * size * 4) as usize],
+        &TextureOptions {
+            srgb: false,
+            ..Default::default()
+        },
+    )
+}
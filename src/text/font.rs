@@ -0,0 +1,477 @@
+use std::sync::Arc;
+
+#[cfg(feature = "ttf")]
+use std::path::Path;
+
+#[cfg(feature = "ttf")]
+use ab_glyph::{Font as _, ScaleFont};
+
+use crate::decode::Image;
+use crate::{Error, Result};
+
+const DEFAULT_GLYPH_WIDTH: u32 = 6;
+const DEFAULT_GLYPH_HEIGHT: u32 = 10;
+const DEFAULT_BASELINE: u32 = 8;
+const DEFAULT_GLYPHS: [[u8; DEFAULT_GLYPH_HEIGHT as usize]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x20, 0x00, 0x00],
+    [0x00, 0x50, 0x50, 0x50, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x50, 0x50, 0xf8, 0x50, 0xf8, 0x50, 0x50, 0x00, 0x00],
+    [0x00, 0x20, 0x70, 0xa0, 0x70, 0x28, 0x70, 0x20, 0x00, 0x00],
+    [0x00, 0x48, 0xa8, 0x50, 0x20, 0x50, 0xa8, 0x90, 0x00, 0x00],
+    [0x00, 0x40, 0xa0, 0xa0, 0x40, 0xa8, 0x90, 0x68, 0x00, 0x00],
+    [0x00, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x10, 0x20, 0x40, 0x40, 0x40, 0x20, 0x10, 0x00, 0x00],
+    [0x00, 0x40, 0x20, 0x10, 0x10, 0x10, 0x20, 0x40, 0x00, 0x00],
+    [0x00, 0x00, 0x88, 0x50, 0xf8, 0x50, 0x88, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x20, 0x20, 0xf8, 0x20, 0x20, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x20, 0x40, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x70, 0x20, 0x00],
+    [0x00, 0x08, 0x08, 0x10, 0x20, 0x40, 0x80, 0x80, 0x00, 0x00],
+    [0x00, 0x20, 0x50, 0x88, 0x88, 0x88, 0x50, 0x20, 0x00, 0x00],
+    [0x00, 0x20, 0x60, 0xa0, 0x20, 0x20, 0x20, 0xf8, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x08, 0x30, 0x40, 0x80, 0xf8, 0x00, 0x00],
+    [0x00, 0xf8, 0x08, 0x10, 0x30, 0x08, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x10, 0x30, 0x50, 0x90, 0xf8, 0x10, 0x10, 0x00, 0x00],
+    [0x00, 0xf8, 0x80, 0xb0, 0xc8, 0x08, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x30, 0x40, 0x80, 0xb0, 0xc8, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0xf8, 0x08, 0x10, 0x10, 0x20, 0x40, 0x40, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x88, 0x70, 0x88, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x98, 0x68, 0x08, 0x10, 0x60, 0x00, 0x00],
+    [0x00, 0x00, 0x20, 0x70, 0x20, 0x00, 0x20, 0x70, 0x20, 0x00],
+    [0x00, 0x00, 0x20, 0x70, 0x20, 0x00, 0x30, 0x20, 0x40, 0x00],
+    [0x00, 0x08, 0x10, 0x20, 0x40, 0x20, 0x10, 0x08, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0xf8, 0x00, 0xf8, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x40, 0x20, 0x10, 0x08, 0x10, 0x20, 0x40, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x10, 0x20, 0x20, 0x00, 0x20, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x98, 0xa8, 0xb0, 0x80, 0x70, 0x00, 0x00],
+    [0x00, 0x20, 0x50, 0x88, 0x88, 0xf8, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0xf0, 0x48, 0x48, 0x70, 0x48, 0x48, 0xf0, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x80, 0x80, 0x80, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0xf0, 0x48, 0x48, 0x48, 0x48, 0x48, 0xf0, 0x00, 0x00],
+    [0x00, 0xf8, 0x80, 0x80, 0xf0, 0x80, 0x80, 0xf8, 0x00, 0x00],
+    [0x00, 0xf8, 0x80, 0x80, 0xf0, 0x80, 0x80, 0x80, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x80, 0x80, 0x98, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0x88, 0xf8, 0x88, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0x70, 0x20, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00],
+    [0x00, 0x38, 0x10, 0x10, 0x10, 0x10, 0x90, 0x60, 0x00, 0x00],
+    [0x00, 0x88, 0x90, 0xa0, 0xc0, 0xa0, 0x90, 0x88, 0x00, 0x00],
+    [0x00, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0xf8, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0xd8, 0xa8, 0x88, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0xc8, 0xa8, 0x98, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0xf0, 0x88, 0x88, 0xf0, 0x80, 0x80, 0x80, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x88, 0x88, 0x88, 0xa8, 0x70, 0x08, 0x00],
+    [0x00, 0xf0, 0x88, 0x88, 0xf0, 0xa0, 0x90, 0x88, 0x00, 0x00],
+    [0x00, 0x70, 0x88, 0x80, 0x70, 0x08, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0xf8, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0x88, 0x50, 0x50, 0x50, 0x20, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0x88, 0xa8, 0xa8, 0xd8, 0x88, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0x50, 0x20, 0x50, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0x88, 0x88, 0x50, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00],
+    [0x00, 0xf8, 0x08, 0x10, 0x20, 0x40, 0x80, 0xf8, 0x00, 0x00],
+    [0x00, 0x70, 0x40, 0x40, 0x40, 0x40, 0x40, 0x70, 0x00, 0x00],
+    [0x00, 0x80, 0x80, 0x40, 0x20, 0x10, 0x08, 0x08, 0x00, 0x00],
+    [0x00, 0x70, 0x10, 0x10, 0x10, 0x10, 0x10, 0x70, 0x00, 0x00],
+    [0x00, 0x20, 0x50, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf8, 0x00],
+    [0x20, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00],
+    [0x00, 0x80, 0x80, 0xb0, 0xc8, 0x88, 0xc8, 0xb0, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x70, 0x88, 0x80, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x08, 0x08, 0x68, 0x98, 0x88, 0x98, 0x68, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x70, 0x88, 0xf8, 0x80, 0x70, 0x00, 0x00],
+    [0x00, 0x30, 0x48, 0x40, 0xf0, 0x40, 0x40, 0x40, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x78, 0x88, 0x88, 0x78, 0x08, 0x88, 0x70],
+    [0x00, 0x80, 0x80, 0xb0, 0xc8, 0x88, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0x20, 0x00, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00],
+    [0x00, 0x08, 0x00, 0x18, 0x08, 0x08, 0x08, 0x48, 0x48, 0x30],
+    [0x00, 0x80, 0x80, 0x88, 0x90, 0xe0, 0x90, 0x88, 0x00, 0x00],
+    [0x00, 0x60, 0x20, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0xd0, 0xa8, 0xa8, 0xa8, 0x88, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0xb0, 0xc8, 0x88, 0x88, 0x88, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0xb0, 0xc8, 0x88, 0xc8, 0xb0, 0x80, 0x80],
+    [0x00, 0x00, 0x00, 0x68, 0x98, 0x88, 0x98, 0x68, 0x08, 0x08],
+    [0x00, 0x00, 0x00, 0xb0, 0xc8, 0x80, 0x80, 0x80, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x70, 0x80, 0x70, 0x08, 0xf0, 0x00, 0x00],
+    [0x00, 0x40, 0x40, 0xf0, 0x40, 0x40, 0x48, 0x30, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x88, 0x88, 0x88, 0x98, 0x68, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x88, 0x88, 0x50, 0x50, 0x20, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x88, 0x88, 0xa8, 0xa8, 0x50, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x88, 0x50, 0x20, 0x50, 0x88, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x88, 0x88, 0x98, 0x68, 0x08, 0x88, 0x70],
+    [0x00, 0x00, 0x00, 0xf8, 0x10, 0x20, 0x40, 0xf8, 0x00, 0x00],
+    [0x00, 0x18, 0x20, 0x10, 0x60, 0x10, 0x20, 0x18, 0x00, 0x00],
+    [0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00],
+    [0x00, 0x60, 0x10, 0x20, 0x18, 0x20, 0x10, 0x60, 0x00, 0x00],
+    [0x00, 0x48, 0xa8, 0x90, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+];
+
+#[derive(Clone)]
+pub struct Font {
+    kind: FontKind,
+}
+
+#[derive(Clone)]
+enum FontKind {
+    Bitmap(Arc<BitmapFont>),
+    #[cfg(feature = "ttf")]
+    TrueType(ab_glyph::FontArc),
+}
+
+#[derive(Debug, Clone)]
+pub struct BitmapFont {
+    glyph_width: u32,
+    glyph_height: u32,
+    baseline: u32,
+    first_char: char,
+    glyph_count: u32,
+    coverage: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+pub(crate) struct RasterizedGlyph {
+    pub width: u32,
+    pub height: u32,
+    pub offset: [f32; 2],
+    pub coverage: Vec<u8>,
+}
+
+impl Font {
+    pub fn bitmap(font: BitmapFont) -> Font {
+        Font {
+            kind: FontKind::Bitmap(Arc::new(font)),
+        }
+    }
+    #[cfg(feature = "ttf")]
+    pub fn from_ttf_bytes(bytes: Vec<u8>) -> Result<Font> {
+        let font = ab_glyph::FontArc::try_from_vec(bytes).map_err(|_| Error::InvalidFont)?;
+        Ok(Font {
+            kind: FontKind::TrueType(font),
+        })
+    }
+    #[cfg(feature = "ttf")]
+    pub fn from_ttf_path(path: impl AsRef<Path>) -> Result<Font> {
+        Font::from_ttf_bytes(std::fs::read(path)?)
+    }
+    pub fn is_bitmap(&self) -> bool {
+        matches!(self.kind, FontKind::Bitmap(_))
+    }
+    pub(crate) fn default_size(&self) -> f32 {
+        match &self.kind {
+            FontKind::Bitmap(font) => font.glyph_height as f32,
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(_) => 16.0,
+        }
+    }
+    #[cfg_attr(not(feature = "ttf"), allow(unused_variables))]
+    pub(crate) fn raster_size(&self, size: f32) -> u32 {
+        match &self.kind {
+            FontKind::Bitmap(_) => 0,
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(_) => size.round().max(1.0) as u32,
+        }
+    }
+    pub(crate) fn scale(&self, size: f32) -> f32 {
+        match &self.kind {
+            FontKind::Bitmap(font) => size / font.glyph_height as f32,
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(_) => 1.0,
+        }
+    }
+    pub(crate) fn line_metrics(&self, size: f32) -> LineMetrics {
+        match &self.kind {
+            FontKind::Bitmap(font) => {
+                let scale = self.scale(size);
+                LineMetrics {
+                    ascent: font.baseline as f32 * scale,
+                    descent: -((font.glyph_height - font.baseline) as f32) * scale,
+                    line_gap: 0.0,
+                }
+            }
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(font) => {
+                let font = font.as_scaled(self.raster_size(size) as f32);
+                LineMetrics {
+                    ascent: font.ascent(),
+                    descent: font.descent(),
+                    line_gap: font.line_gap(),
+                }
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "ttf"), allow(unused_variables))]
+    pub(crate) fn advance(&self, character: char, size: f32) -> f32 {
+        match &self.kind {
+            FontKind::Bitmap(font) => font.glyph_width as f32 * self.scale(size),
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(font) => {
+                let font = font.as_scaled(self.raster_size(size) as f32);
+                font.h_advance(font.glyph_id(character))
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "ttf"), allow(unused_variables))]
+    pub(crate) fn kerning(&self, previous: char, character: char, size: f32) -> f32 {
+        match &self.kind {
+            FontKind::Bitmap(_) => 0.0,
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(font) => {
+                let font = font.as_scaled(self.raster_size(size) as f32);
+                font.kern(font.glyph_id(previous), font.glyph_id(character))
+            }
+        }
+    }
+    #[cfg_attr(not(feature = "ttf"), allow(unused_variables))]
+    pub(crate) fn rasterize(&self, character: char, size: f32) -> Option<RasterizedGlyph> {
+        match &self.kind {
+            FontKind::Bitmap(font) => font.rasterize(character),
+            #[cfg(feature = "ttf")]
+            FontKind::TrueType(font) => {
+                let glyph = font
+                    .glyph_id(character)
+                    .with_scale(self.raster_size(size) as f32);
+                let outline = font.outline_glyph(glyph)?;
+                let bounds = outline.px_bounds();
+                let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+                if width == 0 || height == 0 {
+                    return None;
+                }
+                let mut coverage = vec![0u8; (width * height) as usize];
+                outline.draw(|x, y, value| {
+                    if x < width && y < height {
+                        coverage[(y * width + x) as usize] = (value.clamp(0.0, 1.0) * 255.0) as u8;
+                    }
+                });
+                Some(RasterizedGlyph {
+                    width,
+                    height,
+                    offset: [bounds.min.x, bounds.min.y],
+                    coverage,
+                })
+            }
+        }
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Font::bitmap(BitmapFont::default())
+    }
+}
+
+impl BitmapFont {
+    pub fn from_image(
+        image: &Image,
+        glyph_width: u32,
+        glyph_height: u32,
+        first_char: char,
+    ) -> Result<BitmapFont> {
+        if glyph_width == 0
+            || glyph_height == 0
+            || image.pixels.len() != image.width as usize * image.height as usize * 4
+        {
+            return Err(Error::InvalidFont);
+        }
+        let columns = image.width / glyph_width;
+        let rows = image.height / glyph_height;
+        let opaque = image.pixels.chunks_exact(4).all(|pixel| pixel[3] == 255);
+        let mut coverage =
+            Vec::with_capacity((columns * rows * glyph_width * glyph_height) as usize);
+        for index in 0..columns * rows {
+            let (cell_x, cell_y) = (
+                index % columns * glyph_width,
+                index / columns * glyph_height,
+            );
+            for y in cell_y..cell_y + glyph_height {
+                for x in cell_x..cell_x + glyph_width {
+                    let pixel = &image.pixels[((y * image.width + x) * 4) as usize..][..4];
+                    coverage.push(if opaque {
+                        ((pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32) / 3) as u8
+                    } else {
+                        pixel[3]
+                    });
+                }
+            }
+        }
+
+        Ok(BitmapFont {
+            glyph_width,
+            glyph_height,
+            baseline: glyph_height,
+            first_char,
+            glyph_count: columns * rows,
+            coverage,
+        })
+    }
+    pub fn baseline(mut self, baseline: u32) -> Self {
+        self.baseline = baseline.min(self.glyph_height);
+        self
+    }
+    pub const fn glyph_width(&self) -> u32 {
+        self.glyph_width
+    }
+    pub const fn glyph_height(&self) -> u32 {
+        self.glyph_height
+    }
+    pub fn contains(&self, character: char) -> bool {
+        (character as u32)
+            .checked_sub(self.first_char as u32)
+            .is_some_and(|index| index < self.glyph_count)
+    }
+    fn rasterize(&self, character: char) -> Option<RasterizedGlyph> {
+        let character = if self.contains(character) {
+            character
+        } else if self.contains('?') {
+            '?'
+        } else {
+            return None;
+        };
+        let size = (self.glyph_width * self.glyph_height) as usize;
+        let index = (character as u32 - self.first_char as u32) as usize;
+        let coverage = self.coverage[index * size..][..size].to_vec();
+        if coverage.iter().all(|&value| value == 0) {
+            return None;
+        }
+
+        Some(RasterizedGlyph {
+            width: self.glyph_width,
+            height: self.glyph_height,
+            offset: [0.0, -(self.baseline as f32)],
+            coverage,
+        })
+    }
+}
+
+impl Default for BitmapFont {
+    fn default() -> Self {
+        let mut coverage = Vec::with_capacity(
+            DEFAULT_GLYPHS.len() * (DEFAULT_GLYPH_WIDTH * DEFAULT_GLYPH_HEIGHT) as usize,
+        );
+        for glyph in DEFAULT_GLYPHS {
+            for row in glyph {
+                for x in 0..DEFAULT_GLYPH_WIDTH {
+                    coverage.push(if row & (0x80 >> x) != 0 { 255 } else { 0 });
+                }
+            }
+        }
+
+        BitmapFont {
+            glyph_width: DEFAULT_GLYPH_WIDTH,
+            glyph_height: DEFAULT_GLYPH_HEIGHT,
+            baseline: DEFAULT_BASELINE,
+            first_char: ' ',
+            glyph_count: DEFAULT_GLYPHS.len() as u32,
+            coverage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph_image(alpha: bool) -> Image {
+        let values = [10, 20, 30, 40, 50, 60, 70, 0];
+        let pixels = values
+            .iter()
+            .flat_map(|&value| {
+                if alpha {
+                    [255, 255, 255, value]
+                } else {
+                    [value, value, value, 255]
+                }
+            })
+            .collect();
+        Image {
+            width: 4,
+            height: 2,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn default_font_lookup() {
+        let font = BitmapFont::default();
+        assert!(font.contains(' '));
+        assert!(font.contains('A'));
+        assert!(font.contains('~'));
+        assert!(!font.contains('\u{7f}'));
+        assert!(!font.contains('\u{1f}'));
+        assert!(!font.contains('é'));
+    }
+
+    #[test]
+    fn default_font_rasterize() {
+        let font = BitmapFont::default();
+        let glyph = font.rasterize('A').unwrap();
+        assert_eq!((glyph.width, glyph.height), (6, 10));
+        assert_eq!(glyph.offset, [0.0, -8.0]);
+        assert_eq!(&glyph.coverage[6..12], [0, 0, 255, 0, 0, 0]);
+        assert!(font.rasterize(' ').is_none());
+        assert_eq!(
+            font.rasterize('é').unwrap().coverage,
+            font.rasterize('?').unwrap().coverage
+        );
+    }
+
+    #[test]
+    fn bitmap_measurement() {
+        let font = Font::default();
+        assert_eq!(font.default_size(), 10.0);
+        assert_eq!(font.scale(20.0), 2.0);
+        assert_eq!(font.advance('W', 20.0), 12.0);
+        assert_eq!(font.kerning('A', 'V', 20.0), 0.0);
+        assert_eq!(
+            font.line_metrics(20.0),
+            LineMetrics {
+                ascent: 16.0,
+                descent: -4.0,
+                line_gap: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn from_image_reads_cells() {
+        let font = BitmapFont::from_image(&glyph_image(false), 2, 2, 'a').unwrap();
+        assert_eq!(font.coverage, [10, 20, 50, 60, 30, 40, 70, 0]);
+        assert!(font.contains('a') && font.contains('b') && !font.contains('c'));
+        assert_eq!(font.rasterize('b').unwrap().offset, [0.0, -2.0]);
+
+        let font = BitmapFont::from_image(&glyph_image(true), 2, 2, 'a')
+            .unwrap()
+            .baseline(5);
+        assert_eq!(font.coverage, [10, 20, 50, 60, 30, 40, 70, 0]);
+        assert_eq!(font.baseline, 2);
+        assert!(font.rasterize('z').is_none());
+    }
+
+    #[test]
+    fn from_image_rejects_invalid_input() {
+        let image = glyph_image(false);
+        for (width, height) in [(0, 2), (2, 0)] {
+            assert!(matches!(
+                BitmapFont::from_image(&image, width, height, 'a'),
+                Err(Error::InvalidFont)
+            ));
+        }
+        let short = Image {
+            pixels: image.pixels[..12].to_vec(),
+            ..image
+        };
+        assert!(matches!(
+            BitmapFont::from_image(&short, 2, 2, 'a'),
+            Err(Error::InvalidFont)
+        ));
+    }
+}
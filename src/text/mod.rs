@@ -0,0 +1,336 @@
+mod cache;
+mod font;
+
+pub use font::{BitmapFont, Font};
+
+use crate::camera::Camera2D;
+use crate::depth::StencilMode;
+use crate::math::TransformStack;
+use crate::sprite::{Sprite, SpriteBatch};
+use crate::texture::{RenderableTexture, TextureSampler};
+use crate::ArcedRenderDevice;
+
+use cache::GlyphCache;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FontId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Text<'a> {
+    pub content: &'a str,
+    pub position: [f32; 2],
+    pub font: FontId,
+    pub size: Option<f32>,
+    pub color: wgpu::Color,
+    pub align: TextAlign,
+    pub max_width: Option<f32>,
+    pub line_height: f32,
+}
+
+pub struct TextRenderer {
+    batch: SpriteBatch,
+    fonts: Vec<Font>,
+    cache: GlyphCache,
+}
+
+struct Line {
+    content: String,
+    width: f32,
+}
+
+impl<'a> Text<'a> {
+    pub fn new(content: &'a str, x: f32, y: f32) -> Text<'a> {
+        Text {
+            content,
+            position: [x, y],
+            font: FontId::default(),
+            size: None,
+            color: wgpu::Color::WHITE,
+            align: TextAlign::Left,
+            max_width: None,
+            line_height: 1.0,
+        }
+    }
+    pub fn font(mut self, font: FontId) -> Self {
+        self.font = font;
+        self
+    }
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+    pub fn color(mut self, color: wgpu::Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+    pub fn max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+}
+
+impl TextRenderer {
+    pub fn new(device: &ArcedRenderDevice) -> TextRenderer {
+        let mut batch = SpriteBatch::new(device);
+        batch.set_sampler(TextureSampler::nearest(device));
+
+        TextRenderer {
+            batch,
+            fonts: vec![Font::default()],
+            cache: GlyphCache::new(device),
+        }
+    }
+    pub fn add_font(&mut self, font: Font) -> FontId {
+        self.fonts.push(font);
+        FontId(self.fonts.len() - 1)
+    }
+    pub fn font(&self, id: FontId) -> &Font {
+        &self.fonts[id.0]
+    }
+    pub fn set_stencil(&mut self, stencil: StencilMode, reference: u32) {
+        self.batch.set_stencil(stencil, reference);
+    }
+    pub fn set_camera(&mut self, camera: Option<Camera2D>) {
+        self.batch.set_camera(camera);
+    }
+    pub fn transform_stack(&mut self) -> &mut TransformStack {
+        self.batch.transform_stack()
+    }
+    pub fn clear(&mut self) {
+        self.batch.clear();
+    }
+    pub fn is_empty(&self) -> bool {
+        self.batch.is_empty()
+    }
+    pub fn measure(&self, text: &Text) -> (f32, f32) {
+        let font = &self.fonts[text.font.0];
+        let size = text.size.unwrap_or_else(|| font.default_size());
+        let lines = layout_lines(font, text, size);
+        let width = lines.iter().map(|line| line.width).fold(0.0, f32::max);
+        (width, lines.len() as f32 * line_advance(font, text, size))
+    }
+    pub fn draw(&mut self, text: &Text) {
+        let font = &self.fonts[text.font.0];
+        let size = text.size.unwrap_or_else(|| font.default_size());
+        let scale = font.scale(size);
+        let ascent = font.line_metrics(size).ascent;
+        let advance = line_advance(font, text, size);
+
+        for (index, line) in layout_lines(font, text, size).into_iter().enumerate() {
+            let baseline = (text.position[1] + ascent + index as f32 * advance).round();
+            let mut x = text.position[0] + align_offset(text, line.width);
+            let mut previous = None;
+            for character in line.content.chars() {
+                if let Some(previous) = previous {
+                    x += font.kerning(previous, character, size);
+                }
+                if let Some(glyph) = self.cache.glyph(text.font, font, character, size) {
+                    let sprite = Sprite::new(
+                        (x + glyph.offset[0] * scale).round(),
+                        baseline + (glyph.offset[1] * scale).round(),
+                    )
+                    .scale(scale, scale)
+                    .tint(text.color)
+                    .source(glyph.source);
+                    self.batch.draw(self.cache.texture(), sprite);
+                }
+                x += font.advance(character, size);
+                previous = Some(character);
+            }
+        }
+    }
+    pub fn flush(&mut self, target: &RenderableTexture) {
+        self.batch.flush(target);
+    }
+    pub fn flush_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderableTexture,
+    ) {
+        self.batch.flush_with_encoder(encoder, target);
+    }
+}
+
+fn line_advance(font: &Font, text: &Text, size: f32) -> f32 {
+    let metrics = font.line_metrics(size);
+    (metrics.ascent - metrics.descent + metrics.line_gap) * text.line_height
+}
+
+fn align_offset(text: &Text, width: f32) -> f32 {
+    let factor = match text.align {
+        TextAlign::Left => 0.0,
+        TextAlign::Center => 0.5,
+        TextAlign::Right => 1.0,
+    };
+    (text.max_width.unwrap_or(0.0) - width) * factor
+}
+
+fn line_width(font: &Font, content: &str, size: f32) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for character in content.chars() {
+        if let Some(previous) = previous {
+            width += font.kerning(previous, character, size);
+        }
+        width += font.advance(character, size);
+        previous = Some(character);
+    }
+    width
+}
+
+fn layout_lines(font: &Font, text: &Text, size: f32) -> Vec<Line> {
+    let fits = |content: &str| {
+        text.max_width.map_or(true, |max_width| {
+            line_width(font, content, size) <= max_width
+        })
+    };
+
+    let mut lines = Vec::new();
+    for paragraph in text.content.split('\n') {
+        let mut line = String::new();
+        let mut line_start = true;
+        for word in paragraph.split(' ') {
+            let candidate = if line_start {
+                format!("{}{}", line, word)
+            } else {
+                format!("{} {}", line, word)
+            };
+            line_start = false;
+            if fits(&candidate) {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for character in word.chars() {
+                line.push(character);
+                if line.chars().count() > 1 && !fits(&line) {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, character.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+
+    lines
+        .into_iter()
+        .map(|content| {
+            let trimmed = content.trim_end_matches(' ');
+            Line {
+                width: line_width(font, trimmed, size),
+                content,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(content: &str, max_width: Option<f32>) -> Vec<(String, f32)> {
+        let mut text = Text::new(content, 0.0, 0.0);
+        text.max_width = max_width;
+        layout_lines(&Font::default(), &text, 10.0)
+            .into_iter()
+            .map(|line| (line.content, line.width))
+            .collect()
+    }
+
+    fn line(content: &str, width: f32) -> (String, f32) {
+        (String::from(content), width)
+    }
+
+    #[test]
+    fn layout_without_max_width() {
+        assert_eq!(layout("hello world", None), [line("hello world", 66.0)]);
+        assert_eq!(layout("", None), [line("", 0.0)]);
+    }
+
+    #[test]
+    fn layout_splits_newlines() {
+        assert_eq!(
+            layout("a\nbc\n", None),
+            [line("a", 6.0), line("bc", 12.0), line("", 0.0)]
+        );
+        assert_eq!(
+            layout("aa bb\ncc", Some(30.0)),
+            [line("aa bb", 30.0), line("cc", 12.0)]
+        );
+    }
+
+    #[test]
+    fn layout_wraps_words() {
+        assert_eq!(
+            layout("aa bb cc", Some(30.0)),
+            [line("aa bb", 30.0), line("cc", 12.0)]
+        );
+        assert_eq!(
+            layout("one two three", Some(40.0)),
+            [line("one", 18.0), line("two", 18.0), line("three", 30.0)]
+        );
+    }
+
+    #[test]
+    fn layout_breaks_long_words() {
+        assert_eq!(
+            layout("abcdefg", Some(18.0)),
+            [line("abc", 18.0), line("def", 18.0), line("g", 6.0)]
+        );
+        assert_eq!(
+            layout("ab cdefgh", Some(24.0)),
+            [line("ab", 12.0), line("cdef", 24.0), line("gh", 12.0)]
+        );
+        assert_eq!(
+            layout("abc", Some(1.0)),
+            [line("a", 6.0), line("b", 6.0), line("c", 6.0)]
+        );
+    }
+
+    #[test]
+    fn layout_ignores_trailing_spaces_in_width() {
+        assert_eq!(layout("ab  ", None), [line("ab  ", 12.0)]);
+    }
+
+    #[test]
+    fn alignment_offsets() {
+        let mut text = Text::new("", 0.0, 0.0).max_width(30.0);
+        assert_eq!(align_offset(&text, 12.0), 0.0);
+        text = text.align(TextAlign::Center);
+        assert_eq!(align_offset(&text, 12.0), 9.0);
+        text = text.align(TextAlign::Right);
+        assert_eq!(align_offset(&text, 12.0), 18.0);
+
+        let text = Text::new("", 0.0, 0.0).align(TextAlign::Center);
+        assert_eq!(align_offset(&text, 12.0), -6.0);
+    }
+
+    #[test]
+    fn line_advance_scales_with_line_height() {
+        let font = Font::default();
+        assert_eq!(line_advance(&font, &Text::new("", 0.0, 0.0), 10.0), 10.0);
+        assert_eq!(
+            line_advance(&font, &Text::new("", 0.0, 0.0).line_height(1.5), 20.0),
+            30.0
+        );
+    }
+}
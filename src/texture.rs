@@ -1,84 +1,235 @@
-use wgpu::util::DeviceExt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
 
-use crate::{ArcedRenderDevice, RenderSurface};
+use std::path::Path;
+
+use crate::decode::Image;
+use crate::depth::depth_stencil_attachment;
+use crate::{ArcedRenderDevice, Error, RenderDevice, RenderSurface, Result};
 
 pub struct RenderableTexture {
     device: ArcedRenderDevice,
-    texture: wgpu::Texture,
+    texture: Texture,
+    multisampled: Option<wgpu::Texture>,
+    depth: Option<wgpu::Texture>,
 }
 
 pub struct TextureSampler {
     sampler: wgpu::Sampler,
+    builder: TextureSamplerBuilder,
+}
+
+#[derive(Clone)]
+pub struct Texture {
+    inner: Arc<TextureInner>,
+}
+
+struct TextureInner {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    premultiplied: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub srgb: bool,
+    pub premultiply_alpha: bool,
+    pub mipmaps: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextureSamplerBuilder {
+    descriptor: wgpu::SamplerDescriptor<'static>,
+}
+
+#[derive(Default)]
+pub(crate) struct TextureBindGroupCache {
+    cached: Mutex<Option<(wgpu::Id<wgpu::Texture>, wgpu::BindGroup)>>,
 }
 
 impl RenderableTexture {
     pub fn from_surface(device: &ArcedRenderDevice, surface: &RenderSurface) -> RenderableTexture {
-        let texture_data = vec![
-            255u8;
-            (surface.configuration.width
-                * surface.configuration.height
-                * surface.format.target_pixel_byte_cost().unwrap())
-                as usize
-        ];
-        let texture = device.device.create_texture_with_data(
-            &device.queue,
-            &wgpu::TextureDescriptor {
-                view_formats: &[],
-                label: Some("Texture 2D"),
-                mip_level_count: 1,
-                sample_count: 1,
-                size: wgpu::Extent3d {
-                    width: surface.configuration.width,
-                    height: surface.configuration.height,
-                    depth_or_array_layers: 1,
-                },
-                dimension: wgpu::TextureDimension::D2,
-                format: surface.format,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
-            },
-            wgpu::util::TextureDataOrder::LayerMajor,
-            &texture_data,
+        Self::new(
+            device,
+            surface.configuration.width,
+            surface.configuration.height,
+            surface.format,
+        )
+    }
+    pub fn new(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> RenderableTexture {
+        Self::with_mip_levels(device, width, height, format, 1)
+    }
+    pub fn new_with_mipmaps(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> RenderableTexture {
+        Self::with_mip_levels(
+            device,
+            width,
+            height,
+            format,
+            mip_level_count(width, height),
+        )
+    }
+    fn with_mip_levels(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        mip_level_count: u32,
+    ) -> RenderableTexture {
+        let texture = Self::uninitialized(
+            device,
+            width,
+            height,
+            format,
+            mip_level_count,
+            wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
         );
+        texture.clear_pass(1.0, 1.0, 1.0, 1.0);
+        texture
+    }
+    pub(crate) fn uninitialized(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        mip_level_count: u32,
+        usage: wgpu::TextureUsages,
+    ) -> RenderableTexture {
+        let texture = device.device.create_texture(&wgpu::TextureDescriptor {
+            view_formats: &[],
+            label: Some("Texture 2D"),
+            mip_level_count,
+            sample_count: 1,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: usage
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
 
         RenderableTexture {
             device: device.clone(),
-            texture,
+            texture: Texture::from_wgpu_texture(texture),
+            multisampled: None,
+            depth: None,
         }
     }
     pub fn wgpu_texture(&self) -> &wgpu::Texture {
+        self.texture.wgpu_texture()
+    }
+    pub fn texture(&self) -> &Texture {
         &self.texture
     }
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.wgpu_texture()
+            .create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Renderable Texture View"),
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+    }
+    pub fn create_sampled_view(&self) -> wgpu::TextureView {
+        self.wgpu_texture()
+            .create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Renderable Texture Sampled View"),
+                ..Default::default()
+            })
+    }
+    pub fn mip_level_count(&self) -> u32 {
+        self.wgpu_texture().mip_level_count()
+    }
+    pub fn generate_mipmaps(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.device.generate_mipmaps(encoder, self.wgpu_texture());
+    }
+    pub fn width(&self) -> u32 {
+        self.wgpu_texture().width()
+    }
+    pub fn height(&self) -> u32 {
+        self.wgpu_texture().height()
+    }
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.wgpu_texture().format()
+    }
+    pub fn sample_count(&self) -> u32 {
+        self.multisampled
+            .as_ref()
+            .map_or(1, |multisampled| multisampled.sample_count())
+    }
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<()> {
+        if sample_count == self.sample_count() {
+            return Ok(());
+        }
+        let supported = self
+            .device
+            .supports_sample_count(self.format(), sample_count)
+            && self.depth_format().map_or(true, |format| {
+                self.device.supports_sample_count(format, sample_count)
+            });
+        if !supported {
+            return Err(Error::UnsupportedSampleCount(sample_count));
+        }
+        let multisampled = if sample_count > 1 {
+            Some(self.create_attachment("Multisampled Texture 2D", self.format(), sample_count))
+        } else {
+            None
+        };
+        self.multisampled = multisampled;
+        self.set_depth_format(self.depth_format());
+        Ok(())
+    }
+    pub fn multisampled_texture(&self) -> Option<&wgpu::Texture> {
+        self.multisampled.as_ref()
+    }
+    pub fn create_multisampled_view(&self) -> Option<wgpu::TextureView> {
+        self.multisampled.as_ref().map(|multisampled| {
+            multisampled.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Renderable Texture Multisampled View"),
+                ..Default::default()
+            })
+        })
+    }
+    pub fn set_depth_format(&mut self, format: Option<wgpu::TextureFormat>) {
+        self.depth = format
+            .map(|format| self.create_attachment("Depth Texture 2D", format, self.sample_count()));
+    }
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth.as_ref().map(|depth| depth.format())
+    }
+    pub fn depth_texture(&self) -> Option<&wgpu::Texture> {
+        self.depth.as_ref()
+    }
+    pub fn create_depth_view(&self) -> Option<wgpu::TextureView> {
+        self.depth.as_ref().map(|depth| {
+            depth.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Renderable Texture Depth View"),
+                ..Default::default()
+            })
+        })
+    }
     pub fn clear_pass(&self, r: f64, g: f64, b: f64, a: f64) {
-        let view = self.texture.create_view(&wgpu::TextureViewDescriptor {
-            label: Some("Renderable Texture View"),
-            ..Default::default()
-        });
-
         let mut encoder =
             self.device
                 .device
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                     label: Some("Present Framebuffer Command Encoder"),
                 });
-
-        {
-            _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Present Framebuffer Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-        }
-
+        self.clear_pass_with_encoder(&mut encoder, r, g, b, a);
         self.device.queue.submit([encoder.finish()]);
     }
     pub fn clear_pass_with_encoder(
@@ -89,46 +240,687 @@ impl RenderableTexture {
         b: f64,
         a: f64,
     ) {
-        let view = self.texture.create_view(&wgpu::TextureViewDescriptor {
-            label: Some("Renderable Texture View"),
-            ..Default::default()
-        });
+        self.clear_all_with_encoder(encoder, wgpu::Color { r, g, b, a }, 1.0, 0);
+    }
+    pub fn clear_all(&self, color: wgpu::Color, depth: f32, stencil: u32) {
+        let mut encoder =
+            self.device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Clear Command Encoder"),
+                });
+        self.clear_all_with_encoder(&mut encoder, color, depth, stencil);
+        self.device.queue.submit([encoder.finish()]);
+    }
+    pub fn clear_all_with_encoder(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        color: wgpu::Color,
+        depth: f32,
+        stencil: u32,
+    ) {
+        let view = self.create_view();
+        let multisampled_view = self.create_multisampled_view();
+        let depth_view = self.create_depth_view();
 
         {
             _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Present Framebuffer Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
+                color_attachments: &[Some(self.color_attachment(
+                    &view,
+                    multisampled_view.as_ref(),
+                    wgpu::LoadOp::Clear(color),
+                ))],
+                depth_stencil_attachment: depth_view.as_ref().map(|depth_view| {
+                    depth_stencil_attachment(
+                        depth_view,
+                        self.depth_format().unwrap(),
+                        wgpu::LoadOp::Clear(depth),
+                        wgpu::LoadOp::Clear(stencil),
+                    )
+                }),
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
         }
     }
+    pub fn clear_depth_with_encoder(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        depth: f32,
+        stencil: u32,
+    ) {
+        let depth_view = match self.create_depth_view() {
+            Some(depth_view) => depth_view,
+            None => return,
+        };
+
+        _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Clear Depth Render Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(depth_stencil_attachment(
+                &depth_view,
+                self.depth_format().unwrap(),
+                wgpu::LoadOp::Clear(depth),
+                wgpu::LoadOp::Clear(stencil),
+            )),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+    pub(crate) fn color_attachment<'a>(
+        &self,
+        view: &'a wgpu::TextureView,
+        multisampled_view: Option<&'a wgpu::TextureView>,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let (view, resolve_target) = match multisampled_view {
+            Some(multisampled_view) => (multisampled_view, Some(view)),
+            None => (view, None),
+        };
+        wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+    pub(crate) fn depth_attachment<'a>(
+        &self,
+        depth_view: Option<&'a wgpu::TextureView>,
+    ) -> Option<wgpu::RenderPassDepthStencilAttachment<'a>> {
+        Some(depth_stencil_attachment(
+            depth_view?,
+            self.depth_format()?,
+            wgpu::LoadOp::Load,
+            wgpu::LoadOp::Load,
+        ))
+    }
+    fn create_attachment(
+        &self,
+        label: &str,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::Texture {
+        self.device.device.create_texture(&wgpu::TextureDescriptor {
+            view_formats: &[],
+            label: Some(label),
+            mip_level_count: 1,
+            sample_count,
+            size: self.wgpu_texture().size(),
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: if sample_count > 1 {
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+            } else {
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+            },
+        })
+    }
 }
 
-impl TextureSampler {
-    pub fn new(device: &ArcedRenderDevice) -> TextureSampler {
-        let sampler = device.device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Sampler"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
+impl Texture {
+    pub fn from_rgba8(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<Texture> {
+        Texture::from_rgba8_with_options(device, width, height, pixels, &TextureOptions::default())
+    }
+    pub fn from_rgba8_with_options(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture> {
+        let max_size = device.device.limits().max_texture_dimension_2d;
+        if width == 0 || height == 0 || width > max_size || height > max_size {
+            return Err(Error::InvalidTextureSize { width, height });
+        }
+        if Some(pixels.len()) != rgba8_len(width, height) {
+            return Err(Error::InvalidPixelData {
+                width,
+                height,
+                len: pixels.len(),
+            });
+        }
+        Ok(Texture::create_rgba8(
+            device, width, height, pixels, options,
+        ))
+    }
+    pub(crate) fn create_rgba8(
+        device: &ArcedRenderDevice,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        options: &TextureOptions,
+    ) -> Texture {
+        let mut image = Image {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        };
+        if options.premultiply_alpha {
+            image.premultiply_alpha();
+        }
+        let mip_level_count = if options.mipmaps {
+            mip_level_count(width, height)
+        } else {
+            1
+        };
+        let format = if options.srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+        let texture = device.device.create_texture(&wgpu::TextureDescriptor {
+            view_formats: &[],
+            label: Some("Texture 2D"),
+            mip_level_count,
+            sample_count: 1,
+            size,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+        });
+        device.queue.write_texture(
+            texture.as_image_copy(),
+            &image.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: None,
+            },
+            size,
+        );
+        if mip_level_count > 1 {
+            let mut encoder =
+                device
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Mipmap Command Encoder"),
+                    });
+            device.generate_mipmaps(&mut encoder, &texture);
+            device.queue.submit([encoder.finish()]);
+        }
+
+        Texture::new(texture, options.premultiply_alpha)
+    }
+    pub fn from_image(
+        device: &ArcedRenderDevice,
+        image: &Image,
+        options: &TextureOptions,
+    ) -> Result<Texture> {
+        Texture::from_rgba8_with_options(device, image.width, image.height, &image.pixels, options)
+    }
+    pub fn from_image_bytes(
+        device: &ArcedRenderDevice,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture> {
+        Texture::from_image(device, &Image::from_bytes(bytes)?, options)
+    }
+    pub fn from_path(
+        device: &ArcedRenderDevice,
+        path: impl AsRef<Path>,
+        options: &TextureOptions,
+    ) -> Result<Texture> {
+        Texture::from_image(device, &Image::from_path(path)?, options)
+    }
+    pub fn from_wgpu_texture(texture: wgpu::Texture) -> Texture {
+        Texture::new(texture, false)
+    }
+    fn new(texture: wgpu::Texture, premultiplied: bool) -> Texture {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Texture View"),
             ..Default::default()
         });
-        TextureSampler {
-            sampler,
+
+        Texture {
+            inner: Arc::new(TextureInner {
+                texture,
+                view,
+                premultiplied,
+            }),
+        }
+    }
+    pub fn is_premultiplied(&self) -> bool {
+        self.inner.premultiplied
+    }
+    pub fn wgpu_texture(&self) -> &wgpu::Texture {
+        &self.inner.texture
+    }
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.inner.view
+    }
+    pub fn width(&self) -> u32 {
+        self.inner.texture.width()
+    }
+    pub fn height(&self) -> u32 {
+        self.inner.texture.height()
+    }
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.inner.texture.format()
+    }
+    pub fn ptr_eq(&self, other: &Texture) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        TextureOptions {
+            srgb: true,
+            premultiply_alpha: false,
+            mipmaps: false,
         }
     }
+}
+
+struct StagedReadback {
+    buffer: wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    bytes_per_pixel: u32,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+}
+
+#[derive(Default)]
+struct MapState {
+    result: Option<std::result::Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+struct MapFuture {
+    device: ArcedRenderDevice,
+    state: Arc<Mutex<MapState>>,
+}
+
+impl RenderableTexture {
+    pub fn read_pixels(&self) -> Result<Vec<u8>> {
+        self.read_region(0, 0, self.width(), self.height())
+    }
+    pub fn read_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>> {
+        let staged = self.stage_readback(x, y, width, height)?;
+        let slice = staged.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            _ = sender.send(result);
+        });
+        self.device.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().unwrap()?;
+
+        Ok(staged.unpad())
+    }
+    pub async fn read_pixels_async(&self) -> Result<Vec<u8>> {
+        self.read_region_async(0, 0, self.width(), self.height())
+            .await
+    }
+    pub async fn read_region_async(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>> {
+        let staged = self.stage_readback(x, y, width, height)?;
+        let state = Arc::new(Mutex::new(MapState::default()));
+        let callback_state = state.clone();
+        staged
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let mut state = callback_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+        MapFuture {
+            device: self.device.clone(),
+            state,
+        }
+        .await?;
+
+        Ok(staged.unpad())
+    }
+    fn stage_readback(&self, x: u32, y: u32, width: u32, height: u32) -> Result<StagedReadback> {
+        let right = x.checked_add(width).ok_or(Error::RegionOutOfBounds)?;
+        let bottom = y.checked_add(height).ok_or(Error::RegionOutOfBounds)?;
+        if right > self.width() || bottom > self.height() {
+            return Err(Error::RegionOutOfBounds);
+        }
+        let usage = wgpu::TextureUsages::COPY_SRC;
+        if !self.wgpu_texture().usage().contains(usage) {
+            return Err(Error::MissingUsage(usage));
+        }
+        let format = self.format();
+        let bytes_per_pixel = readback_block_size(format)?;
+        let unpadded_bytes_per_row = width * bytes_per_pixel;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.next_multiple_of(alignment);
+
+        let buffer = self.device.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder =
+            self.device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Readback Command Encoder"),
+                });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: self.wgpu_texture(),
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.device.queue.submit([encoder.finish()]);
+
+        Ok(StagedReadback {
+            buffer,
+            format,
+            bytes_per_pixel,
+            width,
+            height,
+            padded_bytes_per_row,
+        })
+    }
+}
+
+impl StagedReadback {
+    fn unpad(self) -> Vec<u8> {
+        let unpadded_bytes_per_row = (self.width * self.bytes_per_pixel) as usize;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        {
+            let mapped = self.buffer.slice(..).get_mapped_range();
+            for row in mapped.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if matches!(
+            self.format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        pixels
+    }
+}
+
+impl Future for MapFuture {
+    type Output = std::result::Result<(), wgpu::BufferAsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.device.device.poll(wgpu::Maintain::Poll);
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl TextureSampler {
+    pub fn new(device: &RenderDevice) -> TextureSampler {
+        TextureSampler::builder().build(device)
+    }
+    pub fn nearest(device: &RenderDevice) -> TextureSampler {
+        TextureSampler::builder()
+            .filter(wgpu::FilterMode::Nearest)
+            .build(device)
+    }
+    pub fn linear(device: &RenderDevice) -> TextureSampler {
+        TextureSampler::builder()
+            .filter(wgpu::FilterMode::Linear)
+            .build(device)
+    }
+    pub fn builder() -> TextureSamplerBuilder {
+        TextureSamplerBuilder::new()
+    }
     pub fn wgpu_sampler(&self) -> &wgpu::Sampler {
         &self.sampler
     }
+    pub fn recreate(&self, device: &RenderDevice) -> TextureSampler {
+        self.builder.build(device)
+    }
+}
+
+impl TextureSamplerBuilder {
+    pub fn new() -> TextureSamplerBuilder {
+        TextureSamplerBuilder {
+            descriptor: wgpu::SamplerDescriptor {
+                label: Some("Sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            },
+        }
+    }
+    pub fn address_mode(self, mode: wgpu::AddressMode) -> Self {
+        self.address_mode_u(mode)
+            .address_mode_v(mode)
+            .address_mode_w(mode)
+    }
+    pub fn address_mode_u(mut self, mode: wgpu::AddressMode) -> Self {
+        self.descriptor.address_mode_u = mode;
+        self
+    }
+    pub fn address_mode_v(mut self, mode: wgpu::AddressMode) -> Self {
+        self.descriptor.address_mode_v = mode;
+        self
+    }
+    pub fn address_mode_w(mut self, mode: wgpu::AddressMode) -> Self {
+        self.descriptor.address_mode_w = mode;
+        self
+    }
+    pub fn filter(self, mode: wgpu::FilterMode) -> Self {
+        self.mag_filter(mode).min_filter(mode).mipmap_filter(mode)
+    }
+    pub fn mag_filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.descriptor.mag_filter = mode;
+        self
+    }
+    pub fn min_filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.descriptor.min_filter = mode;
+        self
+    }
+    pub fn mipmap_filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.descriptor.mipmap_filter = mode;
+        self
+    }
+    pub fn lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.descriptor.lod_min_clamp = min;
+        self.descriptor.lod_max_clamp = max;
+        self
+    }
+    pub fn anisotropy(mut self, clamp: u16) -> Self {
+        self.descriptor.anisotropy_clamp = clamp.max(1);
+        self
+    }
+    pub fn compare(mut self, compare: wgpu::CompareFunction) -> Self {
+        self.descriptor.compare = Some(compare);
+        self
+    }
+    pub fn border_color(mut self, color: wgpu::SamplerBorderColor) -> Self {
+        self.descriptor.border_color = Some(color);
+        self
+    }
+    pub fn build(&self, device: &RenderDevice) -> TextureSampler {
+        let mut descriptor = self.descriptor.clone();
+        if descriptor.anisotropy_clamp > 1 {
+            descriptor.mag_filter = wgpu::FilterMode::Linear;
+            descriptor.min_filter = wgpu::FilterMode::Linear;
+            descriptor.mipmap_filter = wgpu::FilterMode::Linear;
+        }
+        TextureSampler {
+            sampler: device.device.create_sampler(&descriptor),
+            builder: self.clone(),
+        }
+    }
+}
+
+impl Default for TextureSamplerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextureBindGroupCache {
+    pub(crate) fn bind(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        index: u32,
+        texture: &RenderableTexture,
+        create: impl FnOnce() -> wgpu::BindGroup,
+    ) {
+        let id = texture.wgpu_texture().global_id();
+        let mut cached = self.cached.lock().unwrap();
+        if !matches!(&*cached, Some((cached_id, _)) if *cached_id == id) {
+            *cached = Some((id, create()));
+        }
+        if let Some((_, bind_group)) = &*cached {
+            render_pass.set_bind_group(index, bind_group, &[]);
+        }
+    }
+    pub(crate) fn clear(&mut self) {
+        *self.cached.get_mut().unwrap() = None;
+    }
+}
+
+pub(crate) fn create_texture_bind_group_layout(device: &RenderDevice) -> wgpu::BindGroupLayout {
+    device
+        .device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Texture Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+}
+
+pub(crate) fn create_texture_bind_group(
+    device: &RenderDevice,
+    layout: &wgpu::BindGroupLayout,
+    view: &wgpu::TextureView,
+    sampler: &TextureSampler,
+) -> wgpu::BindGroup {
+    device.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Texture Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler.wgpu_sampler()),
+            },
+        ],
+    })
+}
+
+pub(crate) fn rgba8_len(width: u32, height: u32) -> Option<usize> {
+    (width as usize)
+        .checked_mul(height as usize)?
+        .checked_mul(4)
+}
+
+pub(crate) fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+fn readback_block_size(format: wgpu::TextureFormat) -> Result<u32> {
+    match format.block_copy_size(None) {
+        Some(size) if format.block_dimensions() == (1, 1) => Ok(size),
+        _ => Err(Error::UnsupportedFormat(format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readback_block_sizes() {
+        assert_eq!(
+            readback_block_size(wgpu::TextureFormat::Rgba8Unorm).unwrap(),
+            4
+        );
+        assert_eq!(
+            readback_block_size(wgpu::TextureFormat::Rgba16Float).unwrap(),
+            8
+        );
+        for format in [
+            wgpu::TextureFormat::Depth24PlusStencil8,
+            wgpu::TextureFormat::Depth24Plus,
+            wgpu::TextureFormat::Bc1RgbaUnorm,
+        ] {
+            assert!(matches!(
+                readback_block_size(format),
+                Err(Error::UnsupportedFormat(unsupported)) if unsupported == format
+            ));
+        }
+    }
 }